@@ -0,0 +1,171 @@
+use crate::{
+    constraints::distance::DistanceConstraint,
+    core::world::World,
+    geometry::{
+        vector::Vec2,
+        verlet::{VerletObject, Vervelt},
+    },
+};
+
+pub struct Cloth {
+    pub position: Vec2,
+    pub rows: usize,
+    pub columns: usize,
+    pub spacing: f32,
+    pub radius: f32,
+    pub structural_stiffness: f32,
+    pub shear_stiffness: f32,
+    pub bend_stiffness: f32,
+    pub pinned_rows: Vec<usize>,
+    pub tear_strain: Option<f32>,
+}
+
+impl Cloth {
+    pub fn new(position: Vec2, rows: usize, columns: usize, spacing: f32) -> Cloth {
+        Cloth {
+            position,
+            rows,
+            columns,
+            spacing,
+            radius: spacing / 4.0,
+            structural_stiffness: 1.0,
+            shear_stiffness: 0.5,
+            bend_stiffness: 0.2,
+            pinned_rows: Vec::new(),
+            tear_strain: None,
+        }
+    }
+
+    // adds the particles row by row and returns their body indices
+    pub fn build<B: Vervelt + From<VerletObject>>(&self, world: &mut World<B>) -> Vec<usize> {
+        let mut particles = Vec::with_capacity(self.rows * self.columns);
+
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let position = self.position
+                    + Vec2::new(column as f32 * self.spacing, row as f32 * self.spacing);
+                let mut particle = VerletObject::new(position, self.radius);
+                particle.pinned = self.pinned_rows.contains(&row);
+                particles.push(world.add_body(particle.into()));
+            }
+        }
+
+        let at = |row: usize, column: usize| particles[row * self.columns + column];
+
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                // structural
+                if column + 1 < self.columns {
+                    self.link(
+                        world,
+                        at(row, column),
+                        at(row, column + 1),
+                        self.structural_stiffness,
+                    );
+                }
+                if row + 1 < self.rows {
+                    self.link(
+                        world,
+                        at(row, column),
+                        at(row + 1, column),
+                        self.structural_stiffness,
+                    );
+                }
+
+                // shear
+                if column + 1 < self.columns && row + 1 < self.rows {
+                    self.link(
+                        world,
+                        at(row, column),
+                        at(row + 1, column + 1),
+                        self.shear_stiffness,
+                    );
+                    self.link(
+                        world,
+                        at(row, column + 1),
+                        at(row + 1, column),
+                        self.shear_stiffness,
+                    );
+                }
+
+                // bend
+                if column + 2 < self.columns {
+                    self.link(
+                        world,
+                        at(row, column),
+                        at(row, column + 2),
+                        self.bend_stiffness,
+                    );
+                }
+                if row + 2 < self.rows {
+                    self.link(
+                        world,
+                        at(row, column),
+                        at(row + 2, column),
+                        self.bend_stiffness,
+                    );
+                }
+            }
+        }
+
+        particles
+    }
+
+    fn link<B: Vervelt>(&self, world: &mut World<B>, a: usize, b: usize, stiffness: f32) {
+        let mut constraint = DistanceConstraint::between(a, b, stiffness, &world.bodies);
+        constraint.break_threshold.max_strain = self.tear_strain;
+        world.add_constraint(constraint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{builders::cloth::Cloth, core::world::World, geometry::vector::Vec2};
+
+    #[test]
+    fn constraint_count() {
+        let mut world = World::new();
+        let cloth = Cloth::new(Vec2::new(100.0, 100.0), 4, 5, 10.0);
+        let particles = cloth.build(&mut world);
+
+        let structural = 4 * (5 - 1) + 5 * (4 - 1);
+        let shear = 2 * (4 - 1) * (5 - 1);
+        let bend = 4 * (5 - 2) + 5 * (4 - 2);
+
+        assert!(particles.len() == 20);
+        assert!(world.constraints.len() == structural + shear + bend);
+    }
+
+    #[test]
+    fn pinned_rows_hold() {
+        let mut world = World::new();
+        let mut cloth = Cloth::new(Vec2::new(200.0, 100.0), 5, 5, 10.0);
+        cloth.pinned_rows.push(0);
+        let particles = cloth.build(&mut world);
+
+        for _ in 0..60 {
+            world.update(0.016);
+        }
+
+        for (column, index) in particles.iter().take(5).enumerate() {
+            let expected = Vec2::new(200.0 + column as f32 * 10.0, 100.0);
+            assert!(world.bodies[*index].current_position == expected);
+        }
+        assert!(world.bodies[particles[24]].current_position.y > 140.0);
+    }
+
+    #[test]
+    fn tearing_removes_links() {
+        let mut world = World::new();
+        let mut cloth = Cloth::new(Vec2::new(200.0, 100.0), 3, 3, 10.0);
+        cloth.pinned_rows.push(0);
+        cloth.tear_strain = Some(0.5);
+        cloth.build(&mut world);
+        let initial = world.constraints.len();
+
+        world.bodies[8].current_position = Vec2::new(200.0, 250.0);
+        world.update(0.016);
+
+        assert!(world.constraints.len() < initial);
+    }
+}
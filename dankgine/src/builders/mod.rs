@@ -0,0 +1,3 @@
+pub mod cloth;
+pub mod ragdoll;
+pub mod soft_body;
@@ -0,0 +1,183 @@
+use crate::{
+    constraints::{angle::AngleConstraint, distance::DistanceConstraint},
+    core::world::World,
+    geometry::{
+        vector::Vec2,
+        verlet::{VerletObject, Vervelt},
+    },
+};
+use std::f32::consts::PI;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RagdollHandles {
+    pub head: usize,
+    pub neck: usize,
+    pub pelvis: usize,
+    pub left_elbow: usize,
+    pub left_hand: usize,
+    pub right_elbow: usize,
+    pub right_hand: usize,
+    pub left_knee: usize,
+    pub left_foot: usize,
+    pub right_knee: usize,
+    pub right_foot: usize,
+}
+
+pub struct Ragdoll {
+    pub position: Vec2,
+    pub height: f32,
+    pub stiffness: f32,
+    pub joint_stiffness: f32,
+}
+
+impl Ragdoll {
+    // position is the pelvis, the ragdoll spans height from the top of the head to the feet
+    pub fn new(position: Vec2, height: f32) -> Ragdoll {
+        Ragdoll {
+            position,
+            height,
+            stiffness: 1.0,
+            joint_stiffness: 0.5,
+        }
+    }
+
+    pub fn build<B: Vervelt + From<VerletObject>>(&self, world: &mut World<B>) -> RagdollHandles {
+        let limb_radius = self.height * 0.025;
+
+        let handles = RagdollHandles {
+            head: self.particle(world, -0.02, -0.42, self.height * 0.07),
+            neck: self.particle(world, 0.0, -0.32, limb_radius),
+            pelvis: self.particle(world, 0.0, 0.0, limb_radius),
+            left_elbow: self.particle(world, -0.12, -0.18, limb_radius),
+            left_hand: self.particle(world, -0.14, -0.04, limb_radius),
+            right_elbow: self.particle(world, 0.12, -0.18, limb_radius),
+            right_hand: self.particle(world, 0.14, -0.04, limb_radius),
+            left_knee: self.particle(world, -0.06, 0.24, limb_radius),
+            left_foot: self.particle(world, -0.07, 0.48, limb_radius),
+            right_knee: self.particle(world, 0.06, 0.24, limb_radius),
+            right_foot: self.particle(world, 0.07, 0.48, limb_radius),
+        };
+
+        let h = handles;
+        let bones = [
+            (h.head, h.neck),
+            (h.neck, h.pelvis),
+            (h.neck, h.left_elbow),
+            (h.left_elbow, h.left_hand),
+            (h.neck, h.right_elbow),
+            (h.right_elbow, h.right_hand),
+            (h.pelvis, h.left_knee),
+            (h.left_knee, h.left_foot),
+            (h.pelvis, h.right_knee),
+            (h.right_knee, h.right_foot),
+        ];
+        for (a, b) in bones {
+            let bone = DistanceConstraint::between(a, b, self.stiffness, &world.bodies);
+            world.add_constraint(bone);
+        }
+
+        // limits are relative to the rest pose, the right side mirrors the left
+        self.joint(world, h.head, h.neck, h.pelvis, -0.25 * PI, 0.25 * PI);
+        self.joint(world, h.left_elbow, h.neck, h.pelvis, -0.5 * PI, 0.5 * PI);
+        self.joint(world, h.right_elbow, h.neck, h.pelvis, -0.5 * PI, 0.5 * PI);
+        self.joint(
+            world,
+            h.neck,
+            h.left_elbow,
+            h.left_hand,
+            -0.7 * PI,
+            0.2 * PI,
+        );
+        self.joint(
+            world,
+            h.neck,
+            h.right_elbow,
+            h.right_hand,
+            -0.2 * PI,
+            0.7 * PI,
+        );
+        self.joint(world, h.neck, h.pelvis, h.left_knee, -0.2 * PI, 0.4 * PI);
+        self.joint(world, h.neck, h.pelvis, h.right_knee, -0.4 * PI, 0.2 * PI);
+        self.joint(
+            world,
+            h.pelvis,
+            h.left_knee,
+            h.left_foot,
+            -0.05 * PI,
+            0.6 * PI,
+        );
+        self.joint(
+            world,
+            h.pelvis,
+            h.right_knee,
+            h.right_foot,
+            -0.6 * PI,
+            0.05 * PI,
+        );
+
+        handles
+    }
+
+    fn particle<B: Vervelt + From<VerletObject>>(
+        &self,
+        world: &mut World<B>,
+        x: f32,
+        y: f32,
+        radius: f32,
+    ) -> usize {
+        let position = self.position + Vec2::new(x, y) * self.height;
+        world.add_body(VerletObject::new(position, radius).into())
+    }
+
+    fn joint<B: Vervelt>(
+        &self,
+        world: &mut World<B>,
+        a: usize,
+        b: usize,
+        c: usize,
+        min: f32,
+        max: f32,
+    ) {
+        let mut joint = AngleConstraint::new(a, b, c, 0.0, 0.0, self.joint_stiffness);
+        let rest = joint.angle(&world.bodies);
+        joint.min = rest + min;
+        joint.max = rest + max;
+        world.add_angle_constraint(joint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{builders::ragdoll::Ragdoll, core::world::World, geometry::vector::Vec2};
+
+    #[test]
+    fn keeps_its_bones() {
+        let mut world = World::new();
+        let handles = Ragdoll::new(Vec2::new(300.0, 300.0), 100.0).build(&mut world);
+        let lengths: Vec<f32> = world.constraints.iter().map(|bone| bone.length).collect();
+
+        assert!(world.bodies.len() == 11);
+        assert!(world.constraints.len() == 10);
+        assert!(world.bodies[handles.head].current_position.y < 300.0);
+        assert!(world.bodies[handles.left_foot].current_position.y > 300.0);
+
+        for _ in 0..200 {
+            world.update(0.016);
+        }
+
+        for (bone, length) in world.constraints.iter().zip(lengths) {
+            let current = (world.bodies[bone.a].current_position
+                - world.bodies[bone.b].current_position)
+                .length();
+            assert!((current - length).abs() < length * 0.1);
+        }
+
+        // landing on the boundary bends the joints, but only as far as their limits
+        assert!(world.angle_constraints.len() == 9);
+        for joint in world.angle_constraints.iter() {
+            assert!(joint.violation(&world.bodies).abs() < 0.1);
+        }
+    }
+}
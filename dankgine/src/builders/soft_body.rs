@@ -0,0 +1,96 @@
+use crate::{
+    constraints::{distance::DistanceConstraint, pressure::PressureConstraint},
+    core::world::World,
+    geometry::{
+        vector::Vec2,
+        verlet::{VerletObject, Vervelt},
+    },
+};
+use std::f32::consts::PI;
+
+pub struct SoftBody {
+    pub position: Vec2,
+    pub radius: f32,
+    pub segments: usize,
+    pub particle_radius: f32,
+    pub stiffness: f32,
+    pub pressure: f32,
+}
+
+impl SoftBody {
+    pub fn new(position: Vec2, radius: f32, segments: usize) -> SoftBody {
+        SoftBody {
+            position,
+            radius,
+            segments,
+            particle_radius: radius * (PI / segments as f32).sin(),
+            stiffness: 0.5,
+            pressure: 1000.0,
+        }
+    }
+
+    // adds the ring particles in order around the center and returns their body indices
+    pub fn build<B: Vervelt + From<VerletObject>>(&self, world: &mut World<B>) -> Vec<usize> {
+        let mut particles = Vec::with_capacity(self.segments);
+
+        for segment in 0..self.segments {
+            let angle = segment as f32 / self.segments as f32 * 2.0 * PI;
+            let position = self.position + Vec2::new(angle.cos(), angle.sin()) * self.radius;
+            particles
+                .push(world.add_body(VerletObject::new(position, self.particle_radius).into()));
+        }
+
+        for i in 0..self.segments {
+            let next = (i + 1) % self.segments;
+            world.add_constraint(DistanceConstraint::between(
+                particles[i],
+                particles[next],
+                self.stiffness,
+                &world.bodies,
+            ));
+        }
+
+        world.add_pressure_constraint(PressureConstraint::new(
+            particles.clone(),
+            self.pressure,
+            &world.bodies,
+        ));
+
+        particles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{builders::soft_body::SoftBody, core::world::World, geometry::vector::Vec2};
+
+    #[test]
+    fn keeps_volume_when_resting() {
+        let mut world = World::new();
+        SoftBody::new(Vec2::new(300.0, 450.0), 40.0, 16).build(&mut world);
+        let rest_area = world.pressure_constraints[0].rest_area;
+
+        for _ in 0..300 {
+            world.update(0.016);
+        }
+
+        let area = world.pressure_constraints[0].area(&world.bodies);
+        assert!(area > rest_area * 0.6);
+        assert!(area < rest_area * 1.2);
+    }
+
+    #[test]
+    fn collapses_without_pressure() {
+        let mut world = World::new();
+        let mut soft_body = SoftBody::new(Vec2::new(300.0, 450.0), 40.0, 16);
+        soft_body.pressure = 0.0;
+        soft_body.build(&mut world);
+        let rest_area = world.pressure_constraints[0].rest_area;
+
+        for _ in 0..300 {
+            world.update(0.016);
+        }
+
+        assert!(world.pressure_constraints[0].area(&world.bodies) < rest_area * 0.6);
+    }
+}
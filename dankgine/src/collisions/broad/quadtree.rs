@@ -1,4 +1,5 @@
-use crate::geometry::{rectangle::Rectangle, vector::Vec2};
+use crate::geometry::{ray::Ray, rectangle::Rectangle, vector::Vec2};
+use std::{cmp::Ordering, collections::BinaryHeap};
 
 #[derive(Debug)]
 pub struct QuadTree {
@@ -110,7 +111,7 @@ impl QuadTree {
         self.content.clear();
     }
 
-    pub fn query(&mut self, range: &Rectangle) -> Vec<usize> {
+    pub fn query(&self, range: &Rectangle) -> Vec<usize> {
         if !self.bounds.intersects(range) {
             return Vec::new();
         }
@@ -124,19 +125,19 @@ impl QuadTree {
         }
 
         if self.north_west.is_some() {
-            let mut nw_res = self.north_west.as_mut().unwrap().query(range);
+            let mut nw_res = self.north_west.as_ref().unwrap().query(range);
             if !nw_res.is_empty() {
                 result.append(&mut nw_res);
             }
-            let mut ne_res = self.north_east.as_mut().unwrap().query(range);
+            let mut ne_res = self.north_east.as_ref().unwrap().query(range);
             if !ne_res.is_empty() {
                 result.append(&mut ne_res);
             }
-            let mut sw_res = self.south_west.as_mut().unwrap().query(range);
+            let mut sw_res = self.south_west.as_ref().unwrap().query(range);
             if !sw_res.is_empty() {
                 result.append(&mut sw_res);
             }
-            let mut se_res = self.south_east.as_mut().unwrap().query(range);
+            let mut se_res = self.south_east.as_ref().unwrap().query(range);
             if !se_res.is_empty() {
                 result.append(&mut se_res);
             }
@@ -144,8 +145,164 @@ impl QuadTree {
 
         result
     }
+
+    // every index stored in a node the ray passes within max_distance, nodes are grown
+    // by margin so bodies sticking out of their node are not missed
+    pub fn query_ray(&self, ray: &Ray, max_distance: f32, margin: f32) -> Vec<usize> {
+        let grown = Rectangle::new(
+            self.bounds.position.x - margin,
+            self.bounds.position.y - margin,
+            self.bounds.width + margin * 2.0,
+            self.bounds.height + margin * 2.0,
+        );
+        match ray.intersect_rectangle(&grown) {
+            Some(distance) if distance <= max_distance => {}
+            _ => return Vec::new(),
+        }
+
+        let mut result: Vec<usize> = self.content.iter().map(|(index, _)| *index).collect();
+
+        for child in [
+            &self.north_west,
+            &self.north_east,
+            &self.south_west,
+            &self.south_east,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            result.append(&mut child.query_ray(ray, max_distance, margin));
+        }
+
+        result
+    }
+
+    // the k stored points closest to point, nearest first
+    pub fn nearest(&self, point: &Vec2, k: usize) -> Vec<usize> {
+        let mut result = Vec::new();
+        self.best_first(point, |index, _| {
+            if result.len() >= k {
+                return false;
+            }
+            result.push(index);
+            true
+        });
+        result
+    }
+
+    // every stored point within radius of point, nearest first
+    pub fn within_radius(&self, point: &Vec2, radius: f32) -> Vec<usize> {
+        let mut result = Vec::new();
+        self.best_first(point, |index, distance| {
+            if distance > radius {
+                return false;
+            }
+            result.push(index);
+            true
+        });
+        result
+    }
+
+    // pops nodes and points off a heap ordered by their distance to point, a node is only
+    // opened once nothing stored closer is left, stops as soon as visit returns false
+    pub(crate) fn best_first<F: FnMut(usize, f32) -> bool>(&self, point: &Vec2, mut visit: F) {
+        let mut heap = BinaryHeap::new();
+        heap.push(Candidate {
+            distance: distance_to_bounds(&self.bounds, point),
+            entry: Entry::Node(self),
+        });
+
+        while let Some(candidate) = heap.pop() {
+            match candidate.entry {
+                Entry::Point(index) => {
+                    if !visit(index, candidate.distance) {
+                        return;
+                    }
+                }
+                Entry::Node(node) => {
+                    for (index, position) in node.content.iter() {
+                        heap.push(Candidate {
+                            distance: (*position - *point).length(),
+                            entry: Entry::Point(*index),
+                        });
+                    }
+
+                    for child in [
+                        &node.north_west,
+                        &node.north_east,
+                        &node.south_west,
+                        &node.south_east,
+                    ]
+                    .into_iter()
+                    .flatten()
+                    {
+                        heap.push(Candidate {
+                            distance: distance_to_bounds(&child.bounds, point),
+                            entry: Entry::Node(child),
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn distance_to_bounds(bounds: &Rectangle, point: &Vec2) -> f32 {
+    let closest = Vec2::new(
+        point
+            .x
+            .clamp(bounds.position.x, bounds.position.x + bounds.width),
+        point
+            .y
+            .clamp(bounds.position.y, bounds.position.y + bounds.height),
+    );
+    (closest - *point).length()
 }
 
+enum Entry<'a> {
+    Node(&'a QuadTree),
+    Point(usize),
+}
+
+struct Candidate<'a> {
+    distance: f32,
+    entry: Entry<'a>,
+}
+
+impl Candidate<'_> {
+    // points come before nodes at the same distance, lower indices first
+    fn rank(&self) -> usize {
+        match self.entry {
+            Entry::Point(index) => index,
+            Entry::Node(_) => usize::MAX,
+        }
+    }
+}
+
+// reversed so the max-heap pops the closest candidate first
+impl Ord for Candidate<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| other.rank().cmp(&self.rank()))
+    }
+}
+
+impl PartialOrd for Candidate<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Candidate<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate<'_> {}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -775,4 +932,39 @@ mod tests {
             ))
         }
     }
+
+    #[test]
+    fn nearest_neighbours() {
+        let mut q_tree = create_basic_quadtree();
+        let mut points = Vec::new();
+        let mut seed: u32 = 12345;
+        for index in 0..200 {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            let x = (seed >> 8) as f32 % 100.0;
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            let y = (seed >> 8) as f32 % 100.0;
+            points.push(Vec2::new(x, y));
+            q_tree.insert(&points[index], index);
+        }
+
+        let point = Vec2::new(42.0, 57.0);
+        let mut sorted: Vec<usize> = (0..points.len()).collect();
+        sorted.sort_by(|a, b| {
+            (points[*a] - point)
+                .length()
+                .total_cmp(&(points[*b] - point).length())
+                .then(a.cmp(b))
+        });
+
+        assert!(q_tree.nearest(&point, 10) == sorted[..10]);
+        assert!(q_tree.nearest(&point, 1000).len() == points.len());
+
+        let within: Vec<usize> = sorted
+            .iter()
+            .copied()
+            .filter(|index| (points[*index] - point).length() <= 15.0)
+            .collect();
+        assert!(!within.is_empty());
+        assert!(q_tree.within_radius(&point, 15.0) == within);
+    }
 }
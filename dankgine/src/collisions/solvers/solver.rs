@@ -1,16 +1,15 @@
 use crate::{
     collisions::broad::quadtree::QuadTree,
-    geometry::{rectangle::Rectangle, verlet::VerletObject},
+    geometry::{rectangle::Rectangle, verlet::Vervelt},
     utils::get_two_mut,
 };
-use std::collections::HashSet;
 
 #[derive(Serialize, Deserialize, Clone, Copy)]
 pub struct CollisionSolver {}
 
-fn solve_two_circles(i: usize, k: usize, bodies: &mut Vec<VerletObject>) {
+fn solve_two_circles<B: Vervelt>(i: usize, k: usize, bodies: &mut Vec<B>) {
     let (b1, b2) = match get_two_mut(i, k, bodies) {
-        Some((b1, b2)) => (b1, b2),
+        Some((b1, b2)) => (b1.verlet_mut(), b2.verlet_mut()),
         None => return,
     };
 
@@ -22,33 +21,42 @@ fn solve_two_circles(i: usize, k: usize, bodies: &mut Vec<VerletObject>) {
         return;
     }
 
+    let total_inverse_mass = b1.inverse_mass() + b2.inverse_mass();
+    if total_inverse_mass == 0.0 {
+        return;
+    }
+
     if dist < min_dist {
         let n = collision_axis / dist;
         let delta = min_dist - dist;
+        let ratio1 = b1.inverse_mass() / total_inverse_mass;
+        let ratio2 = b2.inverse_mass() / total_inverse_mass;
 
-        b1.current_position = b1.current_position + (n * 0.5 * delta);
-        b2.current_position = b2.current_position - (n * 0.5 * delta);
+        b1.current_position = b1.current_position + (n * ratio1 * delta);
+        b2.current_position = b2.current_position - (n * ratio2 * delta);
     }
 }
 
 #[allow(dead_code)]
-pub fn quadtree_solve(bodies: &mut Vec<VerletObject>) {
-    let mut quadtree = QuadTree::new(Rectangle::new(0.0, 0.0, 600.0, 600.0), 32, 1, 64);
+pub fn quadtree_solve<B: Vervelt>(bodies: &mut Vec<B>, bounds: Rectangle) {
+    let mut quadtree = QuadTree::new(bounds, 32, 1, 64);
 
     for (index, body) in bodies.iter_mut().enumerate() {
-        quadtree.insert(&body.current_position, index);
+        quadtree.insert(&body.verlet().current_position, index);
     }
 
     let len = bodies.len();
     for current_index in 0..len {
-        let body = bodies.get(current_index).unwrap();
+        let body = bodies.get(current_index).unwrap().verlet();
         let range = Rectangle::new(
             body.current_position.x - body.radius * 2.0,
             body.current_position.y - body.radius * 2.0,
             body.radius * 4.0,
             body.radius * 4.0,
         );
-        let possible_collisions = quadtree.query(&range);
+        // sorted so the pair order doesn't depend on how the tree happened to split
+        let mut possible_collisions = quadtree.query(&range);
+        possible_collisions.sort_unstable();
 
         for possible_collision_index in possible_collisions {
             solve_two_circles(current_index, possible_collision_index, bodies);
@@ -57,7 +65,7 @@ pub fn quadtree_solve(bodies: &mut Vec<VerletObject>) {
 }
 
 #[allow(dead_code)]
-pub fn brute_force_solve(bodies: &mut Vec<VerletObject>) {
+pub fn brute_force_solve<B: Vervelt>(bodies: &mut Vec<B>) {
     let len: usize = bodies.len();
 
     for i in 0..len {
@@ -68,21 +76,22 @@ pub fn brute_force_solve(bodies: &mut Vec<VerletObject>) {
 }
 
 #[allow(dead_code)]
-pub fn solve(bodies: &mut Vec<crate::geometry::verlet::VerletObject>) {
+pub fn solve<B: Vervelt>(bodies: &mut Vec<B>) {
     let count = bodies.len();
 
     bodies.sort_by(|a, b| {
-        a.current_position
+        a.verlet()
+            .current_position
             .x
-            .partial_cmp(&b.current_position.x)
+            .partial_cmp(&b.verlet().current_position.x)
             .unwrap()
     });
 
     let mut active_intervall: Option<(f32, f32)> = None;
-    let mut set: HashSet<usize> = HashSet::new();
+    let mut set: Vec<usize> = Vec::new();
 
     for i in 0..count {
-        let current = bodies.get(i).unwrap();
+        let current = bodies.get(i).unwrap().verlet();
         let current_min_x = current.current_position.x - current.radius;
         let current_max_x = current.current_position.x + current.radius;
         match active_intervall {
@@ -93,25 +102,25 @@ pub fn solve(bodies: &mut Vec<crate::geometry::verlet::VerletObject>) {
                         f32::min(intervall.0, current_min_x),
                         f32::max(intervall.1, current_max_x),
                     ));
-                    set.insert(i);
+                    set.push(i);
                 } else {
                     //current object not in active intervall
                     //Narrow Phase: Are they actually colliding?
                     solve_collisions_for_set(&set, bodies);
-                    set = HashSet::new();
-                    set.insert(i);
+                    set = Vec::new();
+                    set.push(i);
                     active_intervall = Some((current_min_x, current_max_x));
                 }
             }
             None => {
                 active_intervall = Some((current_min_x, current_max_x));
-                set.insert(i);
+                set.push(i);
             }
         }
     }
 }
 
-fn solve_collisions_for_set(set: &HashSet<usize>, bodies: &mut Vec<VerletObject>) {
+fn solve_collisions_for_set<B: Vervelt>(set: &[usize], bodies: &mut Vec<B>) {
     for i in set {
         for k in set {
             solve_two_circles(*i, *k, bodies);
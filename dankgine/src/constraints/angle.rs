@@ -0,0 +1,179 @@
+use crate::{
+    constraints::breaking::BreakThreshold,
+    geometry::{vector::Vec2, verlet::Vervelt},
+};
+use std::f32::consts::PI;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct AngleConstraint {
+    pub a: usize,
+    pub b: usize,
+    pub c: usize,
+    pub min: f32,
+    pub max: f32,
+    pub stiffness: f32,
+    #[serde(default)]
+    pub break_threshold: BreakThreshold,
+}
+
+impl AngleConstraint {
+    pub fn new(
+        a: usize,
+        b: usize,
+        c: usize,
+        min: f32,
+        max: f32,
+        stiffness: f32,
+    ) -> AngleConstraint {
+        AngleConstraint {
+            a,
+            b,
+            c,
+            min,
+            max,
+            stiffness,
+            break_threshold: BreakThreshold::default(),
+        }
+    }
+
+    // signed angle at b going from a to c, in (-PI, PI]
+    pub fn angle<B: Vervelt>(&self, bodies: &[B]) -> f32 {
+        let pivot = bodies[self.b].verlet().current_position;
+        let ba = bodies[self.a].verlet().current_position - pivot;
+        let bc = bodies[self.c].verlet().current_position - pivot;
+        ba.cross(bc).atan2(ba.dot(bc))
+    }
+
+    // how far the angle is past its limits, zero while inside [min, max]
+    // the limits are measured around the middle of the range, so they may wrap past PI
+    pub fn violation<B: Vervelt>(&self, bodies: &[B]) -> f32 {
+        let center = (self.min + self.max) / 2.0;
+        let half_range = (self.max - self.min) / 2.0;
+        let offset = wrap_angle(self.angle(bodies) - center);
+        offset - offset.clamp(-half_range, half_range)
+    }
+
+    // strain of an angle constraint is the violation in radians
+    pub fn strain<B: Vervelt>(&self, bodies: &[B]) -> f32 {
+        self.violation(bodies).abs()
+    }
+
+    // force needed to move the ends back along their arcs within one step of dt
+    pub fn force<B: Vervelt>(&self, dt: f32, bodies: &[B]) -> f32 {
+        let pivot = bodies[self.b].verlet().current_position;
+        let arm_a = (bodies[self.a].verlet().current_position - pivot).length();
+        let arm_c = (bodies[self.c].verlet().current_position - pivot).length();
+        self.strain(bodies) * (arm_a + arm_c) / 2.0 * self.stiffness / (dt * dt)
+    }
+
+    pub fn is_broken<B: Vervelt>(&self, dt: f32, bodies: &[B]) -> bool {
+        self.break_threshold
+            .exceeded(self.strain(bodies), self.force(dt, bodies))
+    }
+
+    // rotates a and c around b until the angle is back inside [min, max]
+    pub fn solve<B: Vervelt>(&self, bodies: &mut [B]) {
+        if self.a == self.b || self.b == self.c || self.a == self.c {
+            return;
+        }
+
+        let violation = self.violation(bodies);
+        if violation == 0.0 {
+            return;
+        }
+
+        let inverse_mass_a = bodies[self.a].verlet().inverse_mass();
+        let inverse_mass_c = bodies[self.c].verlet().inverse_mass();
+        let total_inverse_mass = inverse_mass_a + inverse_mass_c;
+        if total_inverse_mass == 0.0 {
+            return;
+        }
+
+        let delta = -violation * self.stiffness;
+        let pivot = bodies[self.b].verlet().current_position;
+        let a_before = bodies[self.a].verlet().current_position;
+        let c_before = bodies[self.c].verlet().current_position;
+
+        let a_after =
+            pivot + (a_before - pivot).rotate(-delta * inverse_mass_a / total_inverse_mass);
+        let c_after =
+            pivot + (c_before - pivot).rotate(delta * inverse_mass_c / total_inverse_mass);
+
+        // translate all three back so the rotation doesn't move their center of mass,
+        // a pinned participant anchors the center of mass so nothing is shifted then
+        let mut shift = Vec2::new(0.0, 0.0);
+        if inverse_mass_a > 0.0
+            && inverse_mass_c > 0.0
+            && bodies[self.b].verlet().inverse_mass() > 0.0
+        {
+            shift = ((a_before - a_after) + (c_before - c_after)) / 3.0;
+        }
+
+        bodies[self.a].verlet_mut().current_position = a_after + shift;
+        bodies[self.b].verlet_mut().current_position = pivot + shift;
+        bodies[self.c].verlet_mut().current_position = c_after + shift;
+    }
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        constraints::angle::AngleConstraint,
+        geometry::{vector::Vec2, verlet::VerletObject},
+    };
+    use std::f32::consts::PI;
+
+    #[test]
+    fn clamps_into_limits() {
+        let mut bodies = vec![
+            VerletObject::new(Vec2::new(10.0, 0.0), 1.0),
+            VerletObject::new(Vec2::new(0.0, 0.0), 1.0),
+            VerletObject::new(Vec2::new(0.0, 10.0), 1.0),
+        ];
+        let constraint = AngleConstraint::new(0, 1, 2, 0.75 * PI, PI, 1.0);
+
+        assert!((constraint.angle(&bodies) - 0.5 * PI).abs() < 1e-4);
+        constraint.solve(&mut bodies);
+        assert!((constraint.angle(&bodies) - 0.75 * PI).abs() < 1e-4);
+        assert!(
+            ((bodies[0].current_position - bodies[1].current_position).length() - 10.0).abs()
+                < 1e-4
+        );
+    }
+
+    #[test]
+    fn pinned_end_stays() {
+        let mut bodies = vec![
+            VerletObject::new(Vec2::new(10.0, 0.0), 1.0),
+            VerletObject::new(Vec2::new(0.0, 0.0), 1.0),
+            VerletObject::new(Vec2::new(0.0, 10.0), 1.0),
+        ];
+        bodies[0].pinned = true;
+        let constraint = AngleConstraint::new(0, 1, 2, -PI, 0.25 * PI, 1.0);
+
+        constraint.solve(&mut bodies);
+        assert!(bodies[0].current_position == Vec2::new(10.0, 0.0));
+        assert!((constraint.angle(&bodies) - 0.25 * PI).abs() < 1e-4);
+    }
+
+    #[test]
+    fn limits_across_pi() {
+        let mut bodies = vec![
+            VerletObject::new(Vec2::new(10.0, 0.0), 1.0),
+            VerletObject::new(Vec2::new(0.0, 0.0), 1.0),
+            VerletObject::new(Vec2::new(-10.0, 1.0), 1.0),
+        ];
+        let constraint = AngleConstraint::new(0, 1, 2, 0.9 * PI, 1.1 * PI, 1.0);
+
+        constraint.solve(&mut bodies);
+        assert!(bodies[2].current_position == Vec2::new(-10.0, 1.0));
+
+        bodies[2].current_position = Vec2::new(0.0, -10.0);
+        constraint.solve(&mut bodies);
+        assert!((constraint.angle(&bodies) + 0.9 * PI).abs() < 1e-4);
+    }
+}
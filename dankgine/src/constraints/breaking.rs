@@ -0,0 +1,93 @@
+use crate::constraints::{angle::AngleConstraint, distance::DistanceConstraint, spring::Spring};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub struct BreakThreshold {
+    pub max_strain: Option<f32>,
+    pub max_force: Option<f32>,
+}
+
+impl BreakThreshold {
+    pub fn new(max_strain: Option<f32>, max_force: Option<f32>) -> BreakThreshold {
+        BreakThreshold {
+            max_strain,
+            max_force,
+        }
+    }
+
+    pub fn exceeded(&self, strain: f32, force: f32) -> bool {
+        self.max_strain.is_some_and(|max| strain > max)
+            || self.max_force.is_some_and(|max| force > max)
+    }
+}
+
+// the removed constraint as it was when it broke
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum BreakEvent {
+    Distance(DistanceConstraint),
+    Spring(Spring),
+    Angle(AngleConstraint),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        constraints::{
+            breaking::{BreakEvent, BreakThreshold},
+            distance::DistanceConstraint,
+            spring::Spring,
+        },
+        core::world::World,
+        geometry::{vector::Vec2, verlet::VerletObject},
+    };
+
+    fn hanging_pair(world: &mut World) -> (usize, usize) {
+        let mut anchor = VerletObject::new(Vec2::new(300.0, 100.0), 2.0);
+        anchor.pinned = true;
+        let a = world.add_body(anchor);
+        let b = world.add_body(VerletObject::new(Vec2::new(300.0, 150.0), 2.0));
+        (a, b)
+    }
+
+    #[test]
+    fn threshold() {
+        let threshold = BreakThreshold::new(Some(0.5), None);
+        assert!(!threshold.exceeded(0.4, 1000.0));
+        assert!(threshold.exceeded(0.6, 0.0));
+        assert!(!BreakThreshold::default().exceeded(100.0, 100.0));
+    }
+
+    #[test]
+    fn spring_breaks_under_load() {
+        let mut world = World::new();
+        let (a, b) = hanging_pair(&mut world);
+        let mut spring = Spring::between(a, b, 200.0, 10.0, &world.bodies);
+        spring.break_threshold.max_force = Some(500.0);
+        world.add_spring(spring);
+
+        let mut events = Vec::new();
+        for _ in 0..60 {
+            world.update(0.016);
+            events.extend(world.break_events.iter().copied());
+        }
+
+        assert!(world.springs.is_empty());
+        assert!(events.len() == 1);
+        assert!(matches!(events[0], BreakEvent::Spring(_)));
+    }
+
+    #[test]
+    fn distance_holds_below_threshold() {
+        let mut world = World::new();
+        let (a, b) = hanging_pair(&mut world);
+        let mut constraint = DistanceConstraint::between(a, b, 1.0, &world.bodies);
+        constraint.break_threshold.max_strain = Some(0.5);
+        world.add_constraint(constraint);
+
+        for _ in 0..60 {
+            world.update(0.016);
+        }
+
+        assert!(world.constraints.len() == 1);
+        assert!(world.break_events.is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{constraints::breaking::BreakThreshold, geometry::verlet::Vervelt, utils::get_two_mut};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct DistanceConstraint {
+    pub a: usize,
+    pub b: usize,
+    pub length: f32,
+    pub stiffness: f32,
+    #[serde(default)]
+    pub break_threshold: BreakThreshold,
+}
+
+impl DistanceConstraint {
+    pub fn new(a: usize, b: usize, length: f32, stiffness: f32) -> DistanceConstraint {
+        DistanceConstraint {
+            a,
+            b,
+            length,
+            stiffness,
+            break_threshold: BreakThreshold::default(),
+        }
+    }
+
+    // rest length is taken from the current distance between the two bodies
+    pub fn between<B: Vervelt>(
+        a: usize,
+        b: usize,
+        stiffness: f32,
+        bodies: &[B],
+    ) -> DistanceConstraint {
+        let length =
+            (bodies[a].verlet().current_position - bodies[b].verlet().current_position).length();
+        DistanceConstraint::new(a, b, length, stiffness)
+    }
+
+    pub fn strain<B: Vervelt>(&self, bodies: &[B]) -> f32 {
+        if self.length == 0.0 {
+            return 0.0;
+        }
+        let dist = (bodies[self.a].verlet().current_position
+            - bodies[self.b].verlet().current_position)
+            .length();
+        (dist - self.length).abs() / self.length
+    }
+
+    // force needed to pull the bodies back to the rest length within one step of dt
+    pub fn force<B: Vervelt>(&self, dt: f32, bodies: &[B]) -> f32 {
+        let dist = (bodies[self.a].verlet().current_position
+            - bodies[self.b].verlet().current_position)
+            .length();
+        (dist - self.length).abs() * self.stiffness / (dt * dt)
+    }
+
+    pub fn is_broken<B: Vervelt>(&self, dt: f32, bodies: &[B]) -> bool {
+        self.break_threshold
+            .exceeded(self.strain(bodies), self.force(dt, bodies))
+    }
+
+    pub fn solve<B: Vervelt>(&self, bodies: &mut Vec<B>) {
+        let (b1, b2) = match get_two_mut(self.a, self.b, bodies) {
+            Some((b1, b2)) => (b1.verlet_mut(), b2.verlet_mut()),
+            None => return,
+        };
+
+        let axis = b1.current_position - b2.current_position;
+        let dist = axis.length();
+        let total_inverse_mass = b1.inverse_mass() + b2.inverse_mass();
+
+        if dist == 0.0 || total_inverse_mass == 0.0 {
+            return;
+        }
+
+        let correction = axis * ((dist - self.length) / dist * self.stiffness);
+        b1.current_position =
+            b1.current_position - correction * (b1.inverse_mass() / total_inverse_mass);
+        b2.current_position =
+            b2.current_position + correction * (b2.inverse_mass() / total_inverse_mass);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod angle;
+pub mod breaking;
+pub mod distance;
+pub mod mouse;
+pub mod pressure;
+pub mod shape_matching;
+pub mod spring;
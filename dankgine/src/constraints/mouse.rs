@@ -0,0 +1,92 @@
+use crate::geometry::{vector::Vec2, verlet::Vervelt};
+
+// pulls the point a body was grabbed at towards a target that follows the cursor,
+// max_force limits how hard
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct MouseJoint {
+    pub body: usize,
+    pub anchor: Vec2,
+    pub target: Vec2,
+    pub stiffness: f32,
+    pub max_force: Option<f32>,
+}
+
+impl MouseJoint {
+    // anchor is the grab point relative to the body's center
+    pub fn new(
+        body: usize,
+        anchor: Vec2,
+        target: Vec2,
+        stiffness: f32,
+        max_force: Option<f32>,
+    ) -> MouseJoint {
+        MouseJoint {
+            body,
+            anchor,
+            target,
+            stiffness,
+            max_force,
+        }
+    }
+
+    // moving the position without the old one leaves the body with the drag velocity,
+    // so it keeps flying when let go
+    pub fn solve<B: Vervelt>(&self, dt: f32, bodies: &mut [B]) {
+        let body = match bodies.get_mut(self.body) {
+            Some(body) => body.verlet_mut(),
+            None => return,
+        };
+
+        let mut correction = (self.target - self.anchor - body.current_position)
+            * (self.stiffness * body.inverse_mass());
+        if let Some(max_force) = self.max_force {
+            let max_correction = max_force * dt * dt;
+            let length = correction.length();
+            if length > max_correction {
+                correction = correction * (max_correction / length);
+            }
+        }
+
+        body.current_position = body.current_position + correction;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{core::world::World, geometry::vector::Vec2, geometry::verlet::VerletObject};
+
+    #[test]
+    fn drags_towards_the_cursor() {
+        let mut world = World::new();
+        let body = world.add_body(VerletObject::new(Vec2::new(300.0, 300.0), 10.0));
+        world.add_body(VerletObject::new(Vec2::new(100.0, 300.0), 10.0));
+
+        assert!(world.begin_drag(Vec2::new(50.0, 50.0), 0.5, None).is_none());
+        assert!(world.begin_drag(Vec2::new(305.0, 300.0), 0.5, None) == Some(body));
+
+        world.update_drag(Vec2::new(400.0, 300.0));
+        for _ in 0..20 {
+            world.update(0.016);
+        }
+        assert!((world.bodies[body].current_position - Vec2::new(395.0, 300.0)).length() < 1.0);
+
+        world.end_drag();
+        world.update(0.016);
+        assert!(world.mouse_joint.is_none());
+        assert!(world.bodies[body].current_position.y > 300.0);
+    }
+
+    #[test]
+    fn max_force_limits_the_pull() {
+        let mut world = World::new();
+        let body = world.add_body(VerletObject::new(Vec2::new(300.0, 300.0), 10.0));
+
+        world.begin_drag(Vec2::new(300.0, 300.0), 1.0, Some(1000.0));
+        world.update_drag(Vec2::new(500.0, 300.0));
+        world.update(0.016);
+
+        // about what a constant acceleration of 1000 gets done from rest
+        let moved = world.bodies[body].current_position.x - 300.0;
+        assert!(moved > 0.0 && moved < 0.5 * 1000.0 * 0.016 * 0.016 * 1.5);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::geometry::{vector::Vec2, verlet::Vervelt};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PressureConstraint {
+    pub particles: Vec<usize>,
+    pub rest_area: f32,
+    pub pressure: f32,
+}
+
+impl PressureConstraint {
+    pub fn new<B: Vervelt>(
+        particles: Vec<usize>,
+        pressure: f32,
+        bodies: &[B],
+    ) -> PressureConstraint {
+        let rest_area = signed_area(&particles, bodies).abs();
+        PressureConstraint {
+            particles,
+            rest_area,
+            pressure,
+        }
+    }
+
+    pub fn area<B: Vervelt>(&self, bodies: &[B]) -> f32 {
+        signed_area(&self.particles, bodies).abs()
+    }
+
+    // pushes every edge outwards proportional to the area the ring has lost
+    pub fn apply<B: Vervelt>(&self, bodies: &mut [B]) {
+        let count = self.particles.len();
+        if count < 3 || self.rest_area == 0.0 {
+            return;
+        }
+
+        let signed = signed_area(&self.particles, bodies);
+        let orientation = if signed < 0.0 { -1.0 } else { 1.0 };
+        let deficit = (self.rest_area - signed.abs()) / self.rest_area;
+
+        for i in 0..count {
+            let a = self.particles[i];
+            let b = self.particles[(i + 1) % count];
+            let edge = bodies[b].verlet().current_position - bodies[a].verlet().current_position;
+
+            // edge rotated by 90 degrees keeps the edge length, so longer edges get more force
+            let normal = Vec2::new(edge.y, -edge.x) * orientation;
+            let force = normal * (self.pressure * deficit * 0.5);
+
+            bodies[a].accelerate(force);
+            bodies[b].accelerate(force);
+        }
+    }
+}
+
+fn signed_area<B: Vervelt>(particles: &[usize], bodies: &[B]) -> f32 {
+    let count = particles.len();
+    let mut area = 0.0;
+
+    for i in 0..count {
+        let p1 = bodies[particles[i]].verlet().current_position;
+        let p2 = bodies[particles[(i + 1) % count]].verlet().current_position;
+        area += p1.x * p2.y - p2.x * p1.y;
+    }
+
+    area / 2.0
+}
@@ -0,0 +1,111 @@
+use crate::geometry::{vector::Vec2, verlet::Vervelt};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ShapeMatchingCluster {
+    pub particles: Vec<usize>,
+    pub rest_shape: Vec<Vec2>,
+    pub stiffness: f32,
+}
+
+impl ShapeMatchingCluster {
+    // records the current layout of the particles as the rest shape
+    pub fn new<B: Vervelt>(
+        particles: Vec<usize>,
+        stiffness: f32,
+        bodies: &[B],
+    ) -> ShapeMatchingCluster {
+        let center = centroid(&particles, bodies);
+        let rest_shape = particles
+            .iter()
+            .map(|index| bodies[*index].verlet().current_position - center)
+            .collect();
+
+        ShapeMatchingCluster {
+            particles,
+            rest_shape,
+            stiffness,
+        }
+    }
+
+    // angle of the rigid rotation that best maps the rest shape onto the current positions
+    pub fn rotation<B: Vervelt>(&self, bodies: &[B]) -> f32 {
+        let center = centroid(&self.particles, bodies);
+        let mut dot = 0.0;
+        let mut cross = 0.0;
+
+        for (index, rest) in self.particles.iter().zip(self.rest_shape.iter()) {
+            let current = bodies[*index].verlet().current_position - center;
+            dot += rest.dot(current);
+            cross += rest.cross(current);
+        }
+
+        cross.atan2(dot)
+    }
+
+    pub fn solve<B: Vervelt>(&self, bodies: &mut [B]) {
+        if self.particles.is_empty() {
+            return;
+        }
+
+        let center = centroid(&self.particles, bodies);
+        let angle = self.rotation(bodies);
+
+        for (index, rest) in self.particles.iter().zip(self.rest_shape.iter()) {
+            let body = bodies[*index].verlet_mut();
+            let goal = center + rest.rotate(angle);
+            body.current_position = body.current_position
+                + (goal - body.current_position) * (self.stiffness * body.inverse_mass());
+        }
+    }
+}
+
+fn centroid<B: Vervelt>(particles: &[usize], bodies: &[B]) -> Vec2 {
+    let mut sum = Vec2::new(0.0, 0.0);
+    for index in particles {
+        sum = sum + bodies[*index].verlet().current_position;
+    }
+    sum / particles.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        constraints::shape_matching::ShapeMatchingCluster,
+        geometry::{vector::Vec2, verlet::VerletObject},
+    };
+
+    fn square() -> Vec<VerletObject> {
+        vec![
+            VerletObject::new(Vec2::new(0.0, 0.0), 1.0),
+            VerletObject::new(Vec2::new(10.0, 0.0), 1.0),
+            VerletObject::new(Vec2::new(10.0, 10.0), 1.0),
+            VerletObject::new(Vec2::new(0.0, 10.0), 1.0),
+        ]
+    }
+
+    #[test]
+    fn follows_rotation() {
+        let mut bodies = square();
+        let cluster = ShapeMatchingCluster::new(vec![0, 1, 2, 3], 1.0, &bodies);
+
+        for body in bodies.iter_mut() {
+            body.current_position = (body.current_position - Vec2::new(5.0, 5.0)).rotate(0.3);
+        }
+
+        assert!((cluster.rotation(&bodies) - 0.3).abs() < 1e-4);
+    }
+
+    #[test]
+    fn restores_rest_shape() {
+        let mut bodies = square();
+        let cluster = ShapeMatchingCluster::new(vec![0, 1, 2, 3], 1.0, &bodies);
+
+        bodies[2].current_position = Vec2::new(6.0, 6.0);
+        cluster.solve(&mut bodies);
+
+        let diagonal = (bodies[2].current_position - bodies[0].current_position).length();
+        let side = (bodies[1].current_position - bodies[0].current_position).length();
+        assert!((diagonal - 200.0_f32.sqrt()).abs() < 1e-3);
+        assert!((side - 10.0).abs() < 1e-3);
+    }
+}
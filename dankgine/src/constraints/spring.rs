@@ -0,0 +1,114 @@
+use crate::{constraints::breaking::BreakThreshold, geometry::verlet::Vervelt};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Spring {
+    pub a: usize,
+    pub b: usize,
+    pub rest_length: f32,
+    pub stiffness: f32,
+    pub damping: f32,
+    #[serde(default)]
+    pub break_threshold: BreakThreshold,
+}
+
+impl Spring {
+    pub fn new(a: usize, b: usize, rest_length: f32, stiffness: f32, damping: f32) -> Spring {
+        Spring {
+            a,
+            b,
+            rest_length,
+            stiffness,
+            damping,
+            break_threshold: BreakThreshold::default(),
+        }
+    }
+
+    // rest length is taken from the current distance between the two bodies
+    pub fn between<B: Vervelt>(
+        a: usize,
+        b: usize,
+        stiffness: f32,
+        damping: f32,
+        bodies: &[B],
+    ) -> Spring {
+        let rest_length =
+            (bodies[a].verlet().current_position - bodies[b].verlet().current_position).length();
+        Spring::new(a, b, rest_length, stiffness, damping)
+    }
+
+    pub fn strain<B: Vervelt>(&self, bodies: &[B]) -> f32 {
+        if self.rest_length == 0.0 {
+            return 0.0;
+        }
+        let dist = (bodies[self.a].verlet().current_position
+            - bodies[self.b].verlet().current_position)
+            .length();
+        (dist - self.rest_length).abs() / self.rest_length
+    }
+
+    // hooke's law along the spring plus damping of the relative velocity along it,
+    // positive values pull the bodies together
+    pub fn force<B: Vervelt>(&self, dt: f32, bodies: &[B]) -> f32 {
+        let axis =
+            bodies[self.b].verlet().current_position - bodies[self.a].verlet().current_position;
+        let dist = axis.length();
+        if dist == 0.0 || dt == 0.0 {
+            return 0.0;
+        }
+
+        let n = axis / dist;
+        let relative_speed =
+            (bodies[self.b].verlet().velocity(dt) - bodies[self.a].verlet().velocity(dt)).dot(n);
+
+        self.stiffness * (dist - self.rest_length) + self.damping * relative_speed
+    }
+
+    pub fn is_broken<B: Vervelt>(&self, dt: f32, bodies: &[B]) -> bool {
+        self.break_threshold
+            .exceeded(self.strain(bodies), self.force(dt, bodies).abs())
+    }
+
+    pub fn apply<B: Vervelt>(&self, dt: f32, bodies: &mut [B]) {
+        if self.a == self.b {
+            return;
+        }
+
+        let axis =
+            bodies[self.b].verlet().current_position - bodies[self.a].verlet().current_position;
+        let dist = axis.length();
+        if dist == 0.0 {
+            return;
+        }
+
+        let force = axis / dist * self.force(dt, bodies);
+        bodies[self.a].accelerate(force);
+        bodies[self.b].accelerate(force * -1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        constraints::spring::Spring,
+        core::world::World,
+        geometry::{vector::Vec2, verlet::VerletObject},
+    };
+
+    #[test]
+    fn settles_at_static_extension() {
+        let mut world = World::new();
+        let mut anchor = VerletObject::new(Vec2::new(300.0, 100.0), 2.0);
+        anchor.pinned = true;
+        let a = world.add_body(anchor);
+        let b = world.add_body(VerletObject::new(Vec2::new(300.0, 150.0), 2.0));
+        world.add_spring(Spring::between(a, b, 200.0, 10.0, &world.bodies));
+
+        for _ in 0..600 {
+            world.update(0.016);
+        }
+
+        // gravity of 1000 against a stiffness of 200 stretches the spring by 5
+        let length = (world.bodies[b].current_position - world.bodies[a].current_position).length();
+        assert!((length - 55.0).abs() < 0.5);
+    }
+}
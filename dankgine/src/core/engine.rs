@@ -1,34 +1,526 @@
-use crate::core::solver::Solver;
+use crate::builders::cloth::Cloth;
+use crate::builders::ragdoll::{Ragdoll, RagdollHandles};
+use crate::builders::soft_body::SoftBody;
+use crate::constraints::angle::AngleConstraint;
+use crate::constraints::breaking::BreakThreshold;
+use crate::constraints::distance::DistanceConstraint;
+use crate::constraints::shape_matching::ShapeMatchingCluster;
+use crate::constraints::spring::Spring;
+use crate::core::solver::Boundary;
+use crate::core::world::World;
+use crate::fields::force_field::{Falloff, ForceField};
+use crate::geometry::integrator::Integrator;
+use crate::geometry::rectangle::Rectangle;
 use crate::geometry::vector::Vec2;
 use crate::geometry::verlet::VerletObject;
 use crate::STATE;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 
-#[derive(Serialize, Deserialize, Default)]
-pub struct State {
-    bodies: Vec<VerletObject>,
+// JsValue can't cross threads so it can't live in STATE, wasm is single threaded anyway
+thread_local! {
+    static USER_VALUES: RefCell<HashMap<usize, JsValue>> = RefCell::new(HashMap::new());
 }
 
-impl State {
-    pub fn new() -> State {
-        State { bodies: Vec::new() }
+// handles from js are checked before anything indexes with them, a panic while STATE is
+// locked would poison it for every later call
+fn check_bodies(world: &World, indices: &[usize]) -> Result<(), JsValue> {
+    match indices.iter().find(|index| **index >= world.bodies.len()) {
+        Some(index) => Err(JsValue::from_str(&format!("body {} doesn't exist", index))),
+        None => Ok(()),
     }
 }
 
 #[allow(dead_code)]
 #[wasm_bindgen]
 pub fn update() -> String {
-    let solver = Solver::new();
-    solver.update(0.016, &mut STATE.lock().unwrap().bodies);
+    let mut world = STATE.lock().unwrap();
+    let dt = world.time_step;
+    world.update(dt);
+    serde_json::to_string(&*world).unwrap()
+}
+
+// advances by the real elapsed time in fixed steps, returns the interpolation alpha
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn advance(elapsed: f32) -> f32 {
+    STATE.lock().unwrap().advance(elapsed)
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn state() -> String {
     serde_json::to_string(&*STATE.lock().unwrap()).unwrap()
 }
 
+// full world state as a Uint8Array for quick saves, undo and rollback
 #[allow(dead_code)]
 #[wasm_bindgen]
-pub fn add_body(x: f32, y: f32, radius: f32) {
+pub fn snapshot() -> Vec<u8> {
+    STATE.lock().unwrap().snapshot()
+}
+
+// throws if the bytes aren't a snapshot of this version, the world is kept as it was then,
+// the js values are dropped like on load_scene since the bodies are replaced
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn restore(bytes: &[u8]) -> Result<(), JsValue> {
+    STATE
+        .lock()
+        .unwrap()
+        .restore(bytes)
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
+    USER_VALUES.with(|values| values.borrow_mut().clear());
+    Ok(())
+}
+
+// replaces the world with the scene, throws if it can't be loaded
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn load_scene(json: &str) -> Result<(), JsValue> {
+    let world = World::from_scene_json(json).map_err(|err| JsValue::from_str(&err.to_string()))?;
+    *STATE.lock().unwrap() = world;
+    USER_VALUES.with(|values| values.borrow_mut().clear());
+    Ok(())
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn scene() -> String {
+    STATE.lock().unwrap().to_scene_json()
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn set_boundary(x: f32, y: f32, radius: f32) {
+    STATE.lock().unwrap().solver.set_boundary(Boundary {
+        center: Vec2::new(x, y),
+        radius,
+    });
+}
+
+// see World::deterministic, needed for lockstep and replays
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn set_deterministic(deterministic: bool) {
+    STATE.lock().unwrap().deterministic = deterministic;
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn state_hash() -> u64 {
+    STATE.lock().unwrap().state_hash()
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn set_time_step(time_step: f32) {
+    if time_step > 0.0 {
+        STATE.lock().unwrap().time_step = time_step;
+    }
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn add_body(x: f32, y: f32, radius: f32) -> usize {
+    STATE
+        .lock()
+        .unwrap()
+        .add_body(VerletObject::new(Vec2::new(x, y), radius))
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn get_velocity(index: usize) -> Vec<f32> {
+    match STATE.lock().unwrap().velocity(index) {
+        Some(velocity) => vec![velocity.x, velocity.y],
+        None => Vec::new(),
+    }
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn set_velocity(index: usize, vx: f32, vy: f32) {
+    STATE.lock().unwrap().set_velocity(index, Vec2::new(vx, vy));
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn apply_impulse(index: usize, ix: f32, iy: f32) {
+    STATE
+        .lock()
+        .unwrap()
+        .apply_impulse(index, Vec2::new(ix, iy));
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn teleport(index: usize, x: f32, y: f32) {
+    STATE.lock().unwrap().teleport(index, Vec2::new(x, y));
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn set_body_damping(index: usize, damping: f32) {
+    if let Some(body) = STATE.lock().unwrap().bodies.get_mut(index) {
+        body.damping = damping;
+    }
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn set_user_data(index: usize, user_data: u64) {
+    if let Some(body) = STATE.lock().unwrap().bodies.get_mut(index) {
+        body.user_data = user_data;
+    }
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn get_user_data(index: usize) -> Option<u64> {
     STATE
         .lock()
         .unwrap()
         .bodies
-        .push(VerletObject::new(Vec2::new(x, y), radius));
+        .get(index)
+        .map(|body| body.user_data)
+}
+
+// arbitrary js value kept next to the body, it is not part of the serialized state
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn set_user_value(index: usize, value: JsValue) {
+    USER_VALUES.with(|values| {
+        let mut values = values.borrow_mut();
+        if value.is_undefined() {
+            values.remove(&index);
+        } else {
+            values.insert(index, value);
+        }
+    });
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn get_user_value(index: usize) -> JsValue {
+    USER_VALUES.with(|values| {
+        values
+            .borrow()
+            .get(&index)
+            .cloned()
+            .unwrap_or(JsValue::UNDEFINED)
+    })
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn set_body_kinematic(index: usize, kinematic: bool) {
+    if let Some(body) = STATE.lock().unwrap().bodies.get_mut(index) {
+        body.kinematic = kinematic;
+        body.kinematic_target = None;
+    }
+}
+
+// call every fixed step with the scripted position of the body
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn move_kinematic(index: usize, x: f32, y: f32) {
+    STATE.lock().unwrap().move_kinematic(index, Vec2::new(x, y));
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn set_body_ccd(index: usize, ccd: bool) {
+    if let Some(body) = STATE.lock().unwrap().bodies.get_mut(index) {
+        body.ccd = ccd;
+    }
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn set_body_layer(index: usize, layer: u32) {
+    if let Some(body) = STATE.lock().unwrap().bodies.get_mut(index) {
+        body.layer = layer;
+    }
+}
+
+// first hit as json, null if nothing was hit
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn raycast(x: f32, y: f32, dx: f32, dy: f32, max_distance: f32, mask: u32) -> String {
+    let hit = STATE
+        .lock()
+        .unwrap()
+        .raycast(Vec2::new(x, y), Vec2::new(dx, dy), max_distance, mask);
+    serde_json::to_string(&hit).unwrap()
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn raycast_all(x: f32, y: f32, dx: f32, dy: f32, max_distance: f32, mask: u32) -> String {
+    let hits =
+        STATE
+            .lock()
+            .unwrap()
+            .raycast_all(Vec2::new(x, y), Vec2::new(dx, dy), max_distance, mask);
+    serde_json::to_string(&hits).unwrap()
+}
+
+// picks the body under the cursor, returns its handle or undefined if there is none
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn begin_drag(x: f32, y: f32, stiffness: Option<f32>, max_force: Option<f32>) -> Option<usize> {
+    STATE.lock().unwrap().begin_drag(
+        Vec2::new(x, y),
+        stiffness.unwrap_or(DEFAULT_DRAG_STIFFNESS),
+        max_force,
+    )
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn update_drag(x: f32, y: f32) {
+    STATE.lock().unwrap().update_drag(Vec2::new(x, y));
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn end_drag() {
+    STATE.lock().unwrap().end_drag();
+}
+
+const DEFAULT_DRAG_STIFFNESS: f32 = 0.2;
+
+// first hit of a circle moved from (x0, y0) to (x1, y1) as json, null if the way is free,
+// a null body means it stopped at the boundary
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn circle_cast(x0: f32, y0: f32, x1: f32, y1: f32, radius: f32, mask: u32) -> String {
+    let hit = STATE
+        .lock()
+        .unwrap()
+        .circle_cast(Vec2::new(x0, y0), Vec2::new(x1, y1), radius, mask);
+    serde_json::to_string(&hit).unwrap()
+}
+
+// overlapping bodies as a json array of {body, user_data}
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn query_point(x: f32, y: f32, mask: u32) -> String {
+    let overlaps = STATE.lock().unwrap().query_point(Vec2::new(x, y), mask);
+    serde_json::to_string(&overlaps).unwrap()
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn query_circle(x: f32, y: f32, radius: f32, mask: u32) -> String {
+    let overlaps = STATE
+        .lock()
+        .unwrap()
+        .query_circle(Vec2::new(x, y), radius, mask);
+    serde_json::to_string(&overlaps).unwrap()
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn query_rectangle(x: f32, y: f32, width: f32, height: f32, mask: u32) -> String {
+    let overlaps = STATE
+        .lock()
+        .unwrap()
+        .query_rectangle(Rectangle::new(x, y, width, height), mask);
+    serde_json::to_string(&overlaps).unwrap()
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn nearest(x: f32, y: f32, k: usize, mask: u32) -> String {
+    let overlaps = STATE.lock().unwrap().nearest(Vec2::new(x, y), k, mask);
+    serde_json::to_string(&overlaps).unwrap()
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn within_radius(x: f32, y: f32, radius: f32, mask: u32) -> String {
+    let overlaps = STATE
+        .lock()
+        .unwrap()
+        .within_radius(Vec2::new(x, y), radius, mask);
+    serde_json::to_string(&overlaps).unwrap()
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn set_air_damping(damping: f32) {
+    STATE.lock().unwrap().solver.set_damping(damping);
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn set_integrator(integrator: Integrator) {
+    STATE.lock().unwrap().solver.set_integrator(integrator);
+}
+
+// throws if a or b doesn't exist
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn add_distance_constraint(
+    a: usize,
+    b: usize,
+    stiffness: f32,
+    max_strain: Option<f32>,
+    max_force: Option<f32>,
+) -> Result<(), JsValue> {
+    let mut world = STATE.lock().unwrap();
+    check_bodies(&world, &[a, b])?;
+    let mut constraint = DistanceConstraint::between(a, b, stiffness, &world.bodies);
+    constraint.break_threshold = BreakThreshold::new(max_strain, max_force);
+    world.add_constraint(constraint);
+    Ok(())
+}
+
+// throws if a, b or c doesn't exist
+#[allow(dead_code, clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn add_angle_constraint(
+    a: usize,
+    b: usize,
+    c: usize,
+    min: f32,
+    max: f32,
+    stiffness: f32,
+    max_strain: Option<f32>,
+    max_force: Option<f32>,
+) -> Result<(), JsValue> {
+    let mut world = STATE.lock().unwrap();
+    check_bodies(&world, &[a, b, c])?;
+    let mut constraint = AngleConstraint::new(a, b, c, min, max, stiffness);
+    constraint.break_threshold = BreakThreshold::new(max_strain, max_force);
+    world.add_angle_constraint(constraint);
+    Ok(())
+}
+
+// throws if a or b doesn't exist
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn add_spring(
+    a: usize,
+    b: usize,
+    stiffness: f32,
+    damping: f32,
+    max_strain: Option<f32>,
+    max_force: Option<f32>,
+) -> Result<(), JsValue> {
+    let mut world = STATE.lock().unwrap();
+    check_bodies(&world, &[a, b])?;
+    let mut spring = Spring::between(a, b, stiffness, damping, &world.bodies);
+    spring.break_threshold = BreakThreshold::new(max_strain, max_force);
+    world.add_spring(spring);
+    Ok(())
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn add_cloth(x: f32, y: f32, rows: usize, columns: usize, spacing: f32, pin_top_row: bool) {
+    let mut cloth = Cloth::new(Vec2::new(x, y), rows, columns, spacing);
+    if pin_top_row {
+        cloth.pinned_rows.push(0);
+    }
+    cloth.build(&mut STATE.lock().unwrap());
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn add_soft_body(x: f32, y: f32, radius: f32, segments: usize) {
+    SoftBody::new(Vec2::new(x, y), radius, segments).build(&mut STATE.lock().unwrap());
+}
+
+// throws if a particle doesn't exist
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn add_cluster(particles: Vec<usize>, stiffness: f32) -> Result<(), JsValue> {
+    let mut world = STATE.lock().unwrap();
+    check_bodies(&world, &particles)?;
+    let cluster = ShapeMatchingCluster::new(particles, stiffness, &world.bodies);
+    world.add_cluster(cluster);
+    Ok(())
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn add_ragdoll(x: f32, y: f32, height: f32) -> RagdollHandles {
+    Ragdoll::new(Vec2::new(x, y), height).build(&mut STATE.lock().unwrap())
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn add_radial_field(x: f32, y: f32, radius: f32, strength: f32, falloff: Falloff) -> usize {
+    STATE.lock().unwrap().add_force_field(ForceField::Radial {
+        center: Vec2::new(x, y),
+        radius,
+        strength,
+        falloff,
+    })
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn add_wind_field(x: f32, y: f32, width: f32, height: f32, ax: f32, ay: f32) -> usize {
+    STATE.lock().unwrap().add_force_field(ForceField::Wind {
+        area: Rectangle::new(x, y, width, height),
+        acceleration: Vec2::new(ax, ay),
+    })
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn add_vortex_field(x: f32, y: f32, radius: f32, strength: f32, falloff: Falloff) -> usize {
+    STATE.lock().unwrap().add_force_field(ForceField::Vortex {
+        center: Vec2::new(x, y),
+        radius,
+        strength,
+        falloff,
+    })
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn set_field_position(index: usize, x: f32, y: f32) {
+    if let Some(field) = STATE.lock().unwrap().force_fields.get_mut(index) {
+        field.set_position(Vec2::new(x, y));
+    }
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn set_field_strength(index: usize, strength: f32) {
+    if let Some(field) = STATE.lock().unwrap().force_fields.get_mut(index) {
+        field.set_strength(strength);
+    }
+}
+
+// later fields shift down by one, like removing from an array on the JS side
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn remove_field(index: usize) {
+    let mut world = STATE.lock().unwrap();
+    if index < world.force_fields.len() {
+        world.force_fields.remove(index);
+    }
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+pub fn explode(
+    x: f32,
+    y: f32,
+    radius: f32,
+    strength: f32,
+    falloff: Falloff,
+    line_of_sight: bool,
+) -> Vec<usize> {
+    STATE
+        .lock()
+        .unwrap()
+        .explode(Vec2::new(x, y), radius, strength, falloff, line_of_sight)
 }
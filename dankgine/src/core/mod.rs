@@ -1,2 +1,5 @@
 pub mod engine;
+pub mod scene;
+pub mod snapshot;
 pub mod solver;
+pub mod world;
@@ -0,0 +1,467 @@
+use crate::{
+    constraints::{
+        angle::AngleConstraint, distance::DistanceConstraint, pressure::PressureConstraint,
+        shape_matching::ShapeMatchingCluster, spring::Spring,
+    },
+    core::{
+        solver::Solver,
+        world::{default_time_step, World},
+    },
+    fields::force_field::ForceField,
+    geometry::{
+        vector::Vec2,
+        verlet::{default_layer, VerletObject, Vervelt},
+    },
+};
+use serde_json::Value;
+use std::fmt;
+
+// Scene format, a json object describing a level rather than a running simulation:
+//
+// {
+//   "version": 2,
+//   "time_step": 0.016,
+//   "deterministic": false,
+//   "solver": {
+//     "gravity": {"x": 0, "y": 1000}, "damping": 0, "sub_steps": 8,
+//     "integrator": "PositionVerlet" | "VelocityVerlet" | "SemiImplicitEuler",
+//     "boundary": {"center": {"x": 300, "y": 300}, "radius": 300}
+//   },
+//   "bodies": [{
+//     "position": {"x": 0, "y": 0}, "radius": 5, "velocity": {"x": 0, "y": 0},
+//     "pinned": false, "kinematic": false, "ccd": false, "damping": 0,
+//     "layer": 1, "user_data": 0
+//   }],
+//   "constraints": [{"a": 0, "b": 1, "length": 10, "stiffness": 1,
+//                    "break_threshold": {"max_strain": null, "max_force": null}}],
+//   "angle_constraints": [{"a": 0, "b": 1, "c": 2, "min": -1, "max": 1, "stiffness": 0.5}],
+//   "springs": [{"a": 0, "b": 1, "rest_length": 10, "stiffness": 100, "damping": 1}],
+//   "pressure_constraints": [{"particles": [0, 1, 2], "rest_area": 50, "pressure": 1000}],
+//   "clusters": [{"particles": [0, 1], "rest_shape": [{"x": -5, "y": 0}, {"x": 5, "y": 0}],
+//                 "stiffness": 0.5}],
+//   "force_fields": [{"Wind": {"area": {...}, "acceleration": {...}}}]
+// }
+//
+// bodies are referenced by their position in the bodies array. Only version and the body
+// position and radius are required, everything else falls back to the defaults above.
+// The time step and boundary radius have to be positive, no solver setting may be nan or
+// infinite, the solver needs at least one sub-step and every cluster needs one rest
+// position per particle.
+// Version 1 is the world state that state() returns, documents without a version are
+// taken to be version 1 and migrated.
+pub const SCENE_VERSION: u32 = 2;
+
+#[derive(Debug, PartialEq)]
+pub enum SceneError {
+    Json(String),
+    UnsupportedVersion(u64),
+    InvalidBody(usize),
+    InvalidTimeStep(f32),
+    InvalidSubSteps,
+    InvalidSetting(&'static str),
+    InvalidCluster(usize),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Json(reason) => write!(f, "invalid scene: {}", reason),
+            SceneError::UnsupportedVersion(version) => write!(
+                f,
+                "scene version {} is not supported, versions 1 to {} are",
+                version, SCENE_VERSION
+            ),
+            SceneError::InvalidBody(index) => {
+                write!(f, "scene refers to body {} which doesn't exist", index)
+            }
+            SceneError::InvalidTimeStep(time_step) => {
+                write!(f, "time step {} has to be positive and finite", time_step)
+            }
+            SceneError::InvalidSubSteps => write!(f, "the solver needs at least one sub-step"),
+            SceneError::InvalidSetting(setting) => write!(f, "the solver {} is unusable", setting),
+            SceneError::InvalidCluster(index) => write!(
+                f,
+                "cluster {} has a different number of particles and rest positions",
+                index
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneBody {
+    pub position: Vec2,
+    pub radius: f32,
+    #[serde(default)]
+    pub velocity: Vec2,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub kinematic: bool,
+    #[serde(default)]
+    pub ccd: bool,
+    #[serde(default)]
+    pub damping: f32,
+    #[serde(default = "default_layer")]
+    pub layer: u32,
+    #[serde(default)]
+    pub user_data: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scene {
+    pub version: u32,
+    #[serde(default = "default_time_step")]
+    pub time_step: f32,
+    #[serde(default = "Solver::new")]
+    pub solver: Solver,
+    #[serde(default)]
+    pub deterministic: bool,
+    #[serde(default)]
+    pub bodies: Vec<SceneBody>,
+    #[serde(default)]
+    pub constraints: Vec<DistanceConstraint>,
+    #[serde(default)]
+    pub angle_constraints: Vec<AngleConstraint>,
+    #[serde(default)]
+    pub springs: Vec<Spring>,
+    #[serde(default)]
+    pub pressure_constraints: Vec<PressureConstraint>,
+    #[serde(default)]
+    pub clusters: Vec<ShapeMatchingCluster>,
+    #[serde(default)]
+    pub force_fields: Vec<ForceField>,
+}
+
+impl Scene {
+    pub fn from_json(json: &str) -> Result<Scene, SceneError> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|err| SceneError::Json(err.to_string()))?;
+        let scene: Scene = serde_json::from_value(migrate(value)?)
+            .map_err(|err| SceneError::Json(err.to_string()))?;
+        scene.validate()?;
+        Ok(scene)
+    }
+
+    // every body a constraint refers to has to exist, the solver indexes bodies directly,
+    // and the time step, sub-steps and cluster shapes have to be usable by the solver
+    fn validate(&self) -> Result<(), SceneError> {
+        if !(self.time_step.is_finite() && self.time_step > 0.0) {
+            return Err(SceneError::InvalidTimeStep(self.time_step));
+        }
+        if self.solver.sub_steps() == 0 {
+            return Err(SceneError::InvalidSubSteps);
+        }
+        if let Some(setting) = self.solver.invalid_setting() {
+            return Err(SceneError::InvalidSetting(setting));
+        }
+        for (index, cluster) in self.clusters.iter().enumerate() {
+            if cluster.rest_shape.len() != cluster.particles.len() {
+                return Err(SceneError::InvalidCluster(index));
+            }
+        }
+
+        let references = self
+            .constraints
+            .iter()
+            .flat_map(|constraint| [constraint.a, constraint.b])
+            .chain(
+                self.angle_constraints
+                    .iter()
+                    .flat_map(|constraint| [constraint.a, constraint.b, constraint.c]),
+            )
+            .chain(self.springs.iter().flat_map(|spring| [spring.a, spring.b]))
+            .chain(
+                self.pressure_constraints
+                    .iter()
+                    .flat_map(|constraint| constraint.particles.iter().copied()),
+            )
+            .chain(
+                self.clusters
+                    .iter()
+                    .flat_map(|cluster| cluster.particles.iter().copied()),
+            );
+
+        for index in references {
+            if index >= self.bodies.len() {
+                return Err(SceneError::InvalidBody(index));
+            }
+        }
+        Ok(())
+    }
+}
+
+// brings a scene of any older version up to the current one, one version at a time
+fn migrate(mut scene: Value) -> Result<Value, SceneError> {
+    if !scene.is_object() {
+        return Err(SceneError::Json("a scene has to be an object".to_string()));
+    }
+
+    let version = match scene.get("version") {
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| SceneError::Json("version is not a number".to_string()))?,
+        None => 1,
+    };
+    let mut version = match u32::try_from(version) {
+        Ok(version) if (1..=SCENE_VERSION).contains(&version) => version,
+        _ => return Err(SceneError::UnsupportedVersion(version)),
+    };
+
+    while version < SCENE_VERSION {
+        scene = match version {
+            1 => migrate_world_state(scene),
+            _ => return Err(SceneError::UnsupportedVersion(version as u64)),
+        };
+        version += 1;
+    }
+
+    scene["version"] = Value::from(SCENE_VERSION);
+    Ok(scene)
+}
+
+// version 1 bodies are full verlet objects, the position is the current one and the
+// velocity is implied by the old position if the state predates the explicit one
+fn migrate_world_state(mut scene: Value) -> Value {
+    let time_step = scene
+        .get("time_step")
+        .and_then(Value::as_f64)
+        .unwrap_or(default_time_step() as f64);
+    let sub_steps = scene
+        .pointer("/solver/sub_steps")
+        .and_then(Value::as_f64)
+        .unwrap_or(8.0);
+    let sub_dt = time_step / sub_steps;
+
+    let bodies = scene.get_mut("bodies").and_then(Value::as_array_mut);
+    for body in bodies.into_iter().flatten() {
+        let body = match body.as_object_mut() {
+            Some(body) => body,
+            None => continue,
+        };
+
+        if let Some(position) = body.remove("current_position") {
+            if !body.contains_key("velocity") {
+                let axis = |value: &Value, axis: &str| value[axis].as_f64().unwrap_or(0.0);
+                let old = body.get("old_position").unwrap_or(&position);
+                let velocity = serde_json::json!({
+                    "x": (axis(&position, "x") - axis(old, "x")) / sub_dt,
+                    "y": (axis(&position, "y") - axis(old, "y")) / sub_dt,
+                });
+                body.insert("velocity".to_string(), velocity);
+            }
+            body.insert("position".to_string(), position);
+        }
+    }
+
+    scene
+}
+
+impl<B: Vervelt + From<VerletObject>> World<B> {
+    pub fn from_scene(scene: Scene) -> World<B> {
+        let mut world = World {
+            solver: scene.solver,
+            time_step: scene.time_step,
+            deterministic: scene.deterministic,
+            constraints: scene.constraints,
+            angle_constraints: scene.angle_constraints,
+            springs: scene.springs,
+            pressure_constraints: scene.pressure_constraints,
+            clusters: scene.clusters,
+            force_fields: scene.force_fields,
+            ..World::default()
+        };
+
+        let sub_dt = world.sub_dt();
+        for body in scene.bodies {
+            let mut object = VerletObject::new(body.position, body.radius);
+            object.pinned = body.pinned;
+            object.kinematic = body.kinematic;
+            object.ccd = body.ccd;
+            object.damping = body.damping;
+            object.layer = body.layer;
+            object.user_data = body.user_data;
+            object.set_velocity(body.velocity, sub_dt);
+            world.add_body(object.into());
+        }
+        world
+    }
+
+    pub fn from_scene_json(json: &str) -> Result<World<B>, SceneError> {
+        Ok(World::from_scene(Scene::from_json(json)?))
+    }
+}
+
+impl<B: Vervelt> World<B> {
+    // the level as it is right now, runtime state like the accumulator is left out
+    pub fn to_scene(&self) -> Scene {
+        let bodies = (0..self.bodies.len())
+            .map(|index| {
+                let body = self.bodies[index].verlet();
+                SceneBody {
+                    position: body.current_position,
+                    radius: body.radius,
+                    velocity: self.velocity(index).unwrap(),
+                    pinned: body.pinned,
+                    kinematic: body.kinematic,
+                    ccd: body.ccd,
+                    damping: body.damping,
+                    layer: body.layer,
+                    user_data: body.user_data,
+                }
+            })
+            .collect();
+
+        Scene {
+            version: SCENE_VERSION,
+            time_step: self.time_step,
+            solver: self.solver,
+            deterministic: self.deterministic,
+            bodies,
+            constraints: self.constraints.clone(),
+            angle_constraints: self.angle_constraints.clone(),
+            springs: self.springs.clone(),
+            pressure_constraints: self.pressure_constraints.clone(),
+            clusters: self.clusters.clone(),
+            force_fields: self.force_fields.clone(),
+        }
+    }
+
+    pub fn to_scene_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_scene()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        builders::soft_body::SoftBody,
+        core::{scene::SceneError, world::World},
+        geometry::{vector::Vec2, verlet::VerletObject},
+    };
+
+    #[test]
+    fn minimal_scene() {
+        let json = r#"{
+            "version": 2,
+            "bodies": [
+                {"position": {"x": 300, "y": 100}, "radius": 5, "velocity": {"x": 50, "y": 0}},
+                {"position": {"x": 300, "y": 120}, "radius": 5, "pinned": true, "user_data": 9}
+            ],
+            "solver": {"sub_steps": 4},
+            "constraints": [{"a": 0, "b": 1, "length": 20, "stiffness": 1}]
+        }"#;
+
+        let world = World::<VerletObject>::from_scene_json(json).unwrap();
+        assert!(world.bodies.len() == 2 && world.constraints.len() == 1);
+        assert!(world.bodies[1].pinned && world.bodies[1].user_data == 9);
+        assert!(world.bodies[0].layer == 1);
+        assert!(world.sub_dt() == 0.004);
+        assert!((world.velocity(0).unwrap() - Vec2::new(50.0, 0.0)).length() < 1e-3);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut world = World::new();
+        SoftBody::new(Vec2::new(300.0, 300.0), 40.0, 12).build(&mut world);
+        for _ in 0..5 {
+            world.update(0.016);
+        }
+
+        let loaded = World::<VerletObject>::from_scene_json(&world.to_scene_json()).unwrap();
+        assert!(loaded.bodies.len() == world.bodies.len());
+        assert!(loaded.pressure_constraints == world.pressure_constraints);
+        for index in 0..world.bodies.len() {
+            let diff = loaded.bodies[index].current_position - world.bodies[index].current_position;
+            assert!(diff.length() == 0.0);
+            let diff = loaded.velocity(index).unwrap() - world.velocity(index).unwrap();
+            assert!(diff.length() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn migrates_world_state() {
+        let mut world = World::new();
+        let body = world.add_body(VerletObject::new(Vec2::new(300.0, 300.0), 5.0));
+        world.set_velocity(body, Vec2::new(0.0, -100.0));
+        let mut state: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&world).unwrap()).unwrap();
+        state["bodies"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("velocity");
+
+        let loaded = World::<VerletObject>::from_scene_json(&state.to_string()).unwrap();
+        assert!(loaded.bodies[0].current_position == Vec2::new(300.0, 300.0));
+        assert!((loaded.velocity(0).unwrap() - Vec2::new(0.0, -100.0)).length() < 1e-2);
+    }
+
+    #[test]
+    fn rejects_bad_scenes() {
+        let newer = r#"{"version": 3}"#;
+        assert!(
+            World::<VerletObject>::from_scene_json(newer).err()
+                == Some(SceneError::UnsupportedVersion(3))
+        );
+
+        for version in ["0", "4294967297"] {
+            let json = format!(r#"{{"version": {}}}"#, version);
+            assert!(
+                World::<VerletObject>::from_scene_json(&json).err()
+                    == Some(SceneError::UnsupportedVersion(version.parse().unwrap()))
+            );
+        }
+
+        let dangling = r#"{"version": 2, "springs": [
+            {"a": 0, "b": 1, "rest_length": 10, "stiffness": 100, "damping": 1}
+        ]}"#;
+        assert!(
+            World::<VerletObject>::from_scene_json(dangling).err()
+                == Some(SceneError::InvalidBody(0))
+        );
+
+        let still = r#"{"version": 2, "time_step": 0}"#;
+        assert!(
+            World::<VerletObject>::from_scene_json(still).err()
+                == Some(SceneError::InvalidTimeStep(0.0))
+        );
+
+        // json has no nan or infinity, but a value too big for an f32 overflows into one
+        let endless = r#"{"version": 2, "time_step": 1e40}"#;
+        assert!(
+            World::<VerletObject>::from_scene_json(endless).err()
+                == Some(SceneError::InvalidTimeStep(f32::INFINITY))
+        );
+
+        let pointless = r#"{"version": 2,
+            "solver": {"boundary": {"center": {"x": 0, "y": 0}, "radius": 0}}
+        }"#;
+        assert!(
+            World::<VerletObject>::from_scene_json(pointless).err()
+                == Some(SceneError::InvalidSetting("boundary radius"))
+        );
+
+        let no_sub_steps = r#"{"version": 2, "solver": {"sub_steps": 0}}"#;
+        assert!(
+            World::<VerletObject>::from_scene_json(no_sub_steps).err()
+                == Some(SceneError::InvalidSubSteps)
+        );
+
+        let misshapen = r#"{"version": 2,
+            "bodies": [
+                {"position": {"x": 0, "y": 0}, "radius": 5},
+                {"position": {"x": 10, "y": 0}, "radius": 5}
+            ],
+            "clusters": [{"particles": [0, 1], "rest_shape": [{"x": -5, "y": 0}], "stiffness": 1}]
+        }"#;
+        assert!(
+            World::<VerletObject>::from_scene_json(misshapen).err()
+                == Some(SceneError::InvalidCluster(0))
+        );
+
+        assert!(matches!(
+            World::<VerletObject>::from_scene_json("[]"),
+            Err(SceneError::Json(_))
+        ));
+    }
+}
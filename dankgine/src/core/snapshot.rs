@@ -0,0 +1,240 @@
+use crate::{core::world::World, geometry::verlet::Vervelt};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fmt, io};
+
+// a snapshot is the magic bytes and the format version followed by the bincode encoded world,
+// bincode is not self describing so any change to the serialized types needs a new version
+const MAGIC: &[u8; 4] = b"DANK";
+pub const SNAPSHOT_VERSION: u16 = 2;
+const HEADER_LENGTH: usize = MAGIC.len() + 2;
+
+#[derive(Debug, PartialEq)]
+pub enum SnapshotError {
+    NotASnapshot,
+    UnsupportedVersion(u16),
+    Corrupt(String),
+    InvalidBody(usize),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::NotASnapshot => write!(f, "not a snapshot"),
+            SnapshotError::UnsupportedVersion(version) => write!(
+                f,
+                "snapshot version {} is not supported, expected {}",
+                version, SNAPSHOT_VERSION
+            ),
+            SnapshotError::Corrupt(reason) => write!(f, "corrupt snapshot: {}", reason),
+            SnapshotError::InvalidBody(index) => {
+                write!(f, "snapshot refers to body {} which doesn't exist", index)
+            }
+        }
+    }
+}
+
+impl<B: Vervelt + Serialize + DeserializeOwned> World<B> {
+    // everything needed to continue the simulation exactly where it was
+    pub fn snapshot(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LENGTH);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, self).unwrap();
+        bytes
+    }
+
+    // the world is left untouched if the snapshot can't be read
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<(), SnapshotError> {
+        if bytes.len() < HEADER_LENGTH || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(SnapshotError::NotASnapshot);
+        }
+
+        let version = u16::from_le_bytes([bytes[MAGIC.len()], bytes[MAGIC.len() + 1]]);
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        let world: World<B> = bincode::deserialize(&bytes[HEADER_LENGTH..])
+            .map_err(|err| SnapshotError::Corrupt(err.to_string()))?;
+        if let Some(index) = world.missing_body() {
+            return Err(SnapshotError::InvalidBody(index));
+        }
+        *self = world;
+        Ok(())
+    }
+
+    // fnv-1a over the simulation state, two worlds hash the same only if their steps
+    // would continue identically, meant to be compared between peers or replays every step.
+    // The leftover frame time and what the last update reported are left out, they depend
+    // on how the frames were cut up rather than on the steps taken
+    pub fn state_hash(&self) -> u64 {
+        let state = (
+            &self.bodies,
+            &self.constraints,
+            &self.angle_constraints,
+            &self.pressure_constraints,
+            &self.clusters,
+            &self.springs,
+            &self.force_fields,
+            &self.solver,
+            self.time_step,
+            &self.mouse_joint,
+        );
+        let mut hasher = Fnv1a(FNV_OFFSET_BASIS);
+        bincode::serialize_into(&mut hasher, &state).unwrap();
+        hasher.0
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+// hashes what is written to it instead of keeping it, std's hashers don't promise
+// the same output across releases
+struct Fnv1a(u64);
+
+impl io::Write for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(FNV_PRIME);
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        builders::cloth::Cloth,
+        constraints::distance::DistanceConstraint,
+        core::{snapshot::SnapshotError, world::World},
+        geometry::{vector::Vec2, verlet::VerletObject},
+    };
+
+    #[test]
+    fn restored_world_continues_identically() {
+        let mut world = World::new();
+        Cloth::new(Vec2::new(200.0, 100.0), 5, 5, 10.0).build(&mut world);
+        let mut ball = VerletObject::new(Vec2::new(230.0, 50.0), 8.0);
+        ball.user_data = 42;
+        world.add_body(ball);
+        for _ in 0..10 {
+            world.update(0.016);
+        }
+
+        let snapshot = world.snapshot();
+        for _ in 0..10 {
+            world.update(0.016);
+        }
+
+        let mut restored = World::new();
+        restored.restore(&snapshot).unwrap();
+        assert!(restored.bodies[25].user_data == 42);
+        for _ in 0..10 {
+            restored.update(0.016);
+        }
+
+        assert!(restored.bodies == world.bodies);
+        assert!(restored.constraints == world.constraints);
+    }
+
+    #[test]
+    fn rejects_foreign_data() {
+        let mut world = World::new();
+        world.add_body(VerletObject::new(Vec2::new(300.0, 300.0), 5.0));
+        let mut snapshot = world.snapshot();
+
+        let mut other = World::new();
+        assert!(other.restore(b"{}") == Err(SnapshotError::NotASnapshot));
+
+        snapshot[4] = 99;
+        assert!(other.restore(&snapshot) == Err(SnapshotError::UnsupportedVersion(99)));
+
+        snapshot[4] = 2;
+        let truncated = &snapshot[..snapshot.len() - 3];
+        assert!(matches!(
+            other.restore(truncated),
+            Err(SnapshotError::Corrupt(_))
+        ));
+        assert!(other.bodies.is_empty());
+
+        other.restore(&snapshot).unwrap();
+        assert!(other.bodies == world.bodies);
+
+        // a constraint left pointing past the bodies would panic on the next step
+        world.add_body(VerletObject::new(Vec2::new(320.0, 300.0), 5.0));
+        world.add_constraint(DistanceConstraint::new(0, 1, 20.0, 1.0));
+        world.bodies.pop();
+        assert!(other.restore(&world.snapshot()) == Err(SnapshotError::InvalidBody(1)));
+        assert!(other.constraints.is_empty());
+    }
+
+    fn run(deterministic: bool, frames: &[f32]) -> Vec<u64> {
+        let mut world = World::new();
+        world.deterministic = deterministic;
+        Cloth::new(Vec2::new(250.0, 100.0), 6, 6, 10.0).build(&mut world);
+        for i in 0..20 {
+            world.add_body(VerletObject::new(
+                Vec2::new(200.0 + i as f32 * 9.0, 50.0),
+                4.0,
+            ));
+        }
+
+        frames
+            .iter()
+            .map(|dt| {
+                world.update(*dt);
+                world.state_hash()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn deterministic_runs_hash_the_same() {
+        let steady = [0.016; 60];
+        let jittery: Vec<f32> = (0..60).map(|i| 0.012 + (i % 5) as f32 * 0.002).collect();
+
+        let hashes = run(true, &steady);
+        assert!(hashes == run(true, &steady));
+        assert!(hashes == run(true, &jittery));
+        assert!(hashes != run(false, &jittery));
+
+        // every step ends up in a different state
+        assert!(hashes.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn hash_ignores_how_frames_were_cut() {
+        let world = || {
+            let mut world = World::new();
+            world.deterministic = true;
+            world.add_body(VerletObject::new(Vec2::new(300.0, 300.0), 5.0));
+            world
+        };
+
+        let mut even = world();
+        for _ in 0..3 {
+            even.advance(0.02);
+        }
+        let mut uneven = world();
+        uneven.advance(0.05);
+
+        assert!(even.accumulator != uneven.accumulator);
+        assert!(even.state_hash() == uneven.state_hash());
+    }
+
+    #[test]
+    fn hash_sees_the_smallest_change() {
+        let mut world = World::new();
+        let body = world.add_body(VerletObject::new(Vec2::new(300.0, 300.0), 5.0));
+        let hash = world.state_hash();
+
+        let x = world.bodies[body].current_position.x;
+        world.bodies[body].current_position.x = f32::from_bits(x.to_bits() + 1);
+        assert!(world.state_hash() != hash);
+    }
+}
@@ -1,47 +1,153 @@
 use crate::{
-    collisions::solvers::solver::quadtree_solve, geometry::vector::Vec2,
-    geometry::verlet::VerletObject,
+    collisions::solvers::solver::quadtree_solve, constraints::breaking::BreakEvent,
+    core::world::World, geometry::integrator::Integrator, geometry::rectangle::Rectangle,
+    geometry::vector::Vec2, geometry::verlet::Vervelt,
 };
 
-#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
 pub struct Solver {
+    #[serde(default = "default_gravity")]
     gravity: Vec2,
+    #[serde(default)]
+    damping: f32,
+    #[serde(default = "default_sub_steps")]
+    sub_steps: usize,
+    #[serde(default)]
+    integrator: Integrator,
+    #[serde(default)]
+    boundary: Boundary,
+}
+
+// circle every body is kept inside of
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Boundary {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+impl Default for Boundary {
+    fn default() -> Boundary {
+        Boundary {
+            center: Vec2::new(300.0, 300.0),
+            radius: 300.0,
+        }
+    }
+}
+
+fn default_gravity() -> Vec2 {
+    Vec2::new(0.0, 1000.0)
+}
+
+fn default_sub_steps() -> usize {
+    8
 }
 
 impl Solver {
     pub fn new() -> Solver {
         Solver {
-            gravity: Vec2::new(0.0, 1000.0),
+            gravity: default_gravity(),
+            damping: 0.0,
+            sub_steps: default_sub_steps(),
+            integrator: Integrator::PositionVerlet,
+            boundary: Boundary::default(),
         }
     }
 
-    pub fn update(self, dt: f32, bodies: &mut Vec<VerletObject>) {
-        let sub_steps: usize = 8;
-        let sub_dt = dt / sub_steps as f32;
-        for _ in 0..sub_steps {
-            self.apply_gravity(bodies);
-            self.apply_constraint(bodies);
-            self.solve_collisions(bodies);
-            self.update_position(sub_dt, bodies);
+    pub fn boundary(self) -> Boundary {
+        self.boundary
+    }
+
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    pub fn sub_steps(self) -> usize {
+        self.sub_steps
+    }
+
+    // name of the first setting the solver can't step with, the boundary has to have a
+    // positive radius and nothing may be nan or infinite
+    pub(crate) fn invalid_setting(self) -> Option<&'static str> {
+        let finite = |v: Vec2| v.x.is_finite() && v.y.is_finite();
+        if !finite(self.gravity) {
+            Some("gravity")
+        } else if !(self.damping.is_finite() && self.damping >= 0.0) {
+            Some("damping")
+        } else if !finite(self.boundary.center) {
+            Some("boundary center")
+        } else if !(self.boundary.radius.is_finite() && self.boundary.radius > 0.0) {
+            Some("boundary radius")
+        } else {
+            None
         }
     }
 
-    fn update_position(&self, dt: f32, bodies: &mut Vec<VerletObject>) {
+    pub fn sub_dt(self, dt: f32) -> f32 {
+        dt / self.sub_steps as f32
+    }
+
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping;
+    }
+
+    pub fn update<B: Vervelt>(self, dt: f32, world: &mut World<B>) {
+        let sub_dt = self.sub_dt(dt);
+        for _ in 0..self.sub_steps {
+            self.break_constraints(sub_dt, world);
+            self.apply_gravity(&mut world.bodies);
+            self.apply_force_fields(world);
+            self.apply_pressure(world);
+            self.apply_springs(sub_dt, world);
+            self.apply_constraint(&mut world.bodies);
+            self.solve_constraints(world);
+            self.solve_mouse_joint(sub_dt, world);
+            self.solve_collisions(&mut world.bodies);
+            self.update_position(sub_dt, &mut world.bodies);
+            self.continuous_collisions(world);
+        }
+    }
+
+    fn update_position<B: Vervelt>(&self, dt: f32, bodies: &mut Vec<B>) {
         for body in bodies {
-            body.update_position(dt);
+            body.update_position(dt, self.damping, self.integrator);
         }
     }
 
-    fn apply_gravity(self, bodies: &mut Vec<VerletObject>) {
+    fn apply_gravity<B: Vervelt>(self, bodies: &mut Vec<B>) {
         for body in bodies {
             body.accelerate(self.gravity);
         }
     }
 
-    fn apply_constraint(self, bodies: &mut Vec<VerletObject>) {
-        let constraint_position = Vec2::new(300.0, 300.0);
-        let radius: f32 = 300.0;
-        for body in bodies {
+    fn apply_force_fields<B: Vervelt>(self, world: &mut World<B>) {
+        for field in world.force_fields.iter() {
+            field.apply(&mut world.bodies);
+        }
+    }
+
+    fn apply_pressure<B: Vervelt>(self, world: &mut World<B>) {
+        for constraint in world.pressure_constraints.iter() {
+            constraint.apply(&mut world.bodies);
+        }
+    }
+
+    fn apply_springs<B: Vervelt>(self, dt: f32, world: &mut World<B>) {
+        for spring in world.springs.iter() {
+            spring.apply(dt, &mut world.bodies);
+        }
+    }
+
+    fn apply_constraint<B: Vervelt>(self, bodies: &mut [B]) {
+        let constraint_position = self.boundary.center;
+        let radius = self.boundary.radius;
+        for body in bodies.iter_mut().map(Vervelt::verlet_mut) {
+            if body.inverse_mass() == 0.0 {
+                continue;
+            }
             let diff = body.current_position - constraint_position;
             let dist = diff.length();
             if dist > radius - body.radius {
@@ -51,7 +157,201 @@ impl Solver {
         }
     }
 
-    fn solve_collisions(self, bodies: &mut Vec<VerletObject>) {
-        quadtree_solve(bodies);
+    fn solve_constraints<B: Vervelt>(self, world: &mut World<B>) {
+        for constraint in world.constraints.iter() {
+            constraint.solve(&mut world.bodies);
+        }
+
+        for constraint in world.angle_constraints.iter() {
+            constraint.solve(&mut world.bodies);
+        }
+
+        for cluster in world.clusters.iter() {
+            cluster.solve(&mut world.bodies);
+        }
+    }
+
+    fn solve_mouse_joint<B: Vervelt>(self, dt: f32, world: &mut World<B>) {
+        if let Some(joint) = world.mouse_joint {
+            joint.solve(dt, &mut world.bodies);
+        }
+    }
+
+    fn break_constraints<B: Vervelt>(self, dt: f32, world: &mut World<B>) {
+        let bodies = &world.bodies;
+        let events = &mut world.break_events;
+
+        world.constraints.retain(|constraint| {
+            let broken = constraint.is_broken(dt, bodies);
+            if broken {
+                events.push(BreakEvent::Distance(*constraint));
+            }
+            !broken
+        });
+
+        world.springs.retain(|spring| {
+            let broken = spring.is_broken(dt, bodies);
+            if broken {
+                events.push(BreakEvent::Spring(*spring));
+            }
+            !broken
+        });
+
+        world.angle_constraints.retain(|constraint| {
+            let broken = constraint.is_broken(dt, bodies);
+            if broken {
+                events.push(BreakEvent::Angle(*constraint));
+            }
+            !broken
+        });
+    }
+
+    fn solve_collisions<B: Vervelt>(self, bodies: &mut Vec<B>) {
+        let bounds = Rectangle::bounding(bodies.iter().map(|body| body.verlet().current_position));
+        quadtree_solve(bodies, bounds);
+    }
+
+    // ccd bodies that moved further than their radius are swept from where they were and
+    // stopped at the first body they entered or at the boundary, bodies they were already
+    // touching only stop them when they move further in
+    fn continuous_collisions<B: Vervelt>(self, world: &mut World<B>) {
+        let mut broad_phase = None;
+        for index in 0..world.bodies.len() {
+            let body = world.bodies[index].verlet();
+            if !body.ccd || body.inverse_mass() == 0.0 {
+                continue;
+            }
+
+            let start = body.old_position;
+            let delta = body.current_position - start;
+            let radius = body.radius;
+            let length = delta.length();
+            if length <= radius {
+                continue;
+            }
+
+            let broad_phase = broad_phase.get_or_insert_with(|| world.broad_phase());
+            let mut time = world
+                .sweep(broad_phase, start, delta, length, radius, u32::MAX)
+                .into_iter()
+                .find(|hit| {
+                    hit.body != index && (hit.distance > 0.0 || delta.dot(hit.normal) < 0.0)
+                })
+                .map_or(1.0, |hit| hit.distance / length);
+            if let Some(exit) = self.boundary.exit(start, delta, radius) {
+                time = time.min(exit);
+            }
+
+            if time < 1.0 {
+                world.bodies[index].verlet_mut().current_position = start + delta * time;
+            }
+        }
+    }
+}
+
+impl Boundary {
+    // fraction of delta after which a circle of radius starting inside the boundary
+    // touches it, none if it stays inside or already started outside
+    pub(crate) fn exit(&self, start: Vec2, delta: Vec2, radius: f32) -> Option<f32> {
+        let limit = self.radius - radius;
+        let offset = start - self.center;
+        let a = delta.dot(delta);
+        let b = 2.0 * offset.dot(delta);
+        let c = offset.dot(offset) - limit * limit;
+        if a == 0.0 || c > 0.0 {
+            return None;
+        }
+
+        let time = (-b + (b * b - 4.0 * a * c).sqrt()) / (2.0 * a);
+        if time < 1.0 {
+            Some(time)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::{solver::Boundary, world::World},
+        geometry::{vector::Vec2, verlet::VerletObject},
+    };
+
+    // a small fast body flying right, through a thin pinned one 100 units ahead if wall
+    fn shoot(ccd: bool, from: Vec2, wall: bool) -> (World, usize) {
+        let mut world = World::new();
+        if wall {
+            let mut wall = VerletObject::new(from + Vec2::new(100.0, 0.0), 2.0);
+            wall.pinned = true;
+            world.add_body(wall);
+        }
+
+        let mut bullet = VerletObject::new(from, 2.0);
+        bullet.ccd = ccd;
+        let bullet = world.add_body(bullet);
+        world.set_velocity(bullet, Vec2::new(20000.0, 0.0));
+        world.update(0.016);
+        (world, bullet)
+    }
+
+    #[test]
+    fn ccd_stops_at_thin_bodies() {
+        let from = Vec2::new(200.0, 300.0);
+
+        let (world, bullet) = shoot(false, from, true);
+        assert!(world.bodies[bullet].current_position.x > 310.0);
+
+        let (world, bullet) = shoot(true, from, true);
+        assert!(world.bodies[bullet].current_position.x < 300.0);
+
+        // once stopped against the wall it keeps pushing into it on the following frames
+        let mut world = World::new();
+        world.solver.gravity = Vec2::new(0.0, 0.0);
+
+        let mut wall = VerletObject::new(from + Vec2::new(100.0, 0.0), 2.0);
+        wall.pinned = true;
+        world.add_body(wall);
+
+        let mut bullet = VerletObject::new(from, 2.0);
+        bullet.ccd = true;
+        let bullet = world.add_body(bullet);
+        world.set_velocity(bullet, Vec2::new(20000.0, 0.0));
+
+        for _ in 0..5 {
+            world.update(0.016);
+            assert!(world.bodies[bullet].current_position.x < 300.0);
+        }
+    }
+
+    #[test]
+    fn collides_anywhere_inside_a_moved_boundary() {
+        let mut world = World::new();
+        world.solver.gravity = Vec2::new(0.0, 0.0);
+        world.solver.set_boundary(Boundary {
+            center: Vec2::new(900.0, 900.0),
+            radius: 300.0,
+        });
+
+        let a = world.add_body(VerletObject::new(Vec2::new(895.0, 900.0), 10.0));
+        let b = world.add_body(VerletObject::new(Vec2::new(905.0, 900.0), 10.0));
+        world.update(0.016);
+
+        let dist = (world.bodies[a].current_position - world.bodies[b].current_position).length();
+        assert!(dist >= 20.0 - 1e-3);
+    }
+
+    // the last sub-step carries the body past the boundary
+    #[test]
+    fn ccd_stays_inside_the_boundary() {
+        let from = Boundary::default().center;
+
+        let (world, bullet) = shoot(false, from, false);
+        let dist = (world.bodies[bullet].current_position - from).length();
+        assert!(dist > 300.0);
+
+        let (world, bullet) = shoot(true, from, false);
+        let dist = (world.bodies[bullet].current_position - from).length();
+        assert!(dist <= 298.0 + 1e-3);
     }
 }
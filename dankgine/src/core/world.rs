@@ -0,0 +1,927 @@
+use crate::{
+    collisions::broad::quadtree::QuadTree,
+    constraints::{
+        angle::AngleConstraint, breaking::BreakEvent, distance::DistanceConstraint,
+        mouse::MouseJoint, pressure::PressureConstraint, shape_matching::ShapeMatchingCluster,
+        spring::Spring,
+    },
+    core::solver::Solver,
+    fields::force_field::{Falloff, ForceField},
+    geometry::{
+        ray::Ray,
+        rectangle::Rectangle,
+        vector::Vec2,
+        verlet::{VerletObject, Vervelt},
+    },
+    queries::{overlap::Overlap, raycast::RaycastHit, shape_cast::ShapeCastHit},
+};
+use std::cell::OnceCell;
+
+#[derive(Serialize, Deserialize)]
+pub struct World<B = VerletObject> {
+    pub bodies: Vec<B>,
+    pub constraints: Vec<DistanceConstraint>,
+    pub angle_constraints: Vec<AngleConstraint>,
+    pub pressure_constraints: Vec<PressureConstraint>,
+    pub clusters: Vec<ShapeMatchingCluster>,
+    pub springs: Vec<Spring>,
+    pub force_fields: Vec<ForceField>,
+    pub solver: Solver,
+    #[serde(default = "default_time_step")]
+    pub time_step: f32,
+    #[serde(default)]
+    pub accumulator: f32,
+    #[serde(default)]
+    pub previous_positions: Vec<Vec2>,
+    #[serde(default)]
+    pub break_events: Vec<BreakEvent>,
+    #[serde(default)]
+    pub mouse_joint: Option<MouseJoint>,
+    // same inputs give bit-identical states on the same build target: every update runs
+    // at time_step whatever dt is passed, bodies, pairs and constraints are always visited
+    // in index order and nothing depends on hashing or allocation, state_hash compares runs.
+    // sin, cos and atan2 come from the platform's libm, so native and wasm may still differ
+    #[serde(default)]
+    pub deterministic: bool,
+    // quadtree the queries share, built by the first query after the bodies last moved,
+    // bodies moved or added through the bodies field need invalidate_queries
+    #[serde(skip)]
+    pub(crate) query_tree: OnceCell<(QuadTree, f32)>,
+}
+
+// plain verlet bodies, worlds of custom bodies are made through World::<B>::default()
+impl World {
+    pub fn new() -> World {
+        World::default()
+    }
+}
+
+impl<B: Vervelt> World<B> {
+    pub fn add_body(&mut self, body: B) -> usize {
+        self.invalidate_queries();
+        self.bodies.push(body);
+        self.bodies.len() - 1
+    }
+
+    pub fn add_constraint(&mut self, constraint: DistanceConstraint) {
+        self.constraints.push(constraint);
+    }
+
+    pub fn add_angle_constraint(&mut self, constraint: AngleConstraint) {
+        self.angle_constraints.push(constraint);
+    }
+
+    pub fn add_pressure_constraint(&mut self, constraint: PressureConstraint) {
+        self.pressure_constraints.push(constraint);
+    }
+
+    pub fn add_cluster(&mut self, cluster: ShapeMatchingCluster) {
+        self.clusters.push(cluster);
+    }
+
+    pub fn add_spring(&mut self, spring: Spring) {
+        self.springs.push(spring);
+    }
+
+    pub fn add_force_field(&mut self, field: ForceField) -> usize {
+        self.force_fields.push(field);
+        self.force_fields.len() - 1
+    }
+
+    // length of one solver sub-step at the world's time step, which is what
+    // the implicit velocity of the bodies is measured against
+    pub fn sub_dt(&self) -> f32 {
+        self.solver.sub_dt(self.time_step)
+    }
+
+    pub fn velocity(&self, index: usize) -> Option<Vec2> {
+        let sub_dt = self.sub_dt();
+        self.bodies
+            .get(index)
+            .map(|body| body.verlet().velocity(sub_dt))
+    }
+
+    pub fn set_velocity(&mut self, index: usize, velocity: Vec2) {
+        let sub_dt = self.sub_dt();
+        if let Some(body) = self.bodies.get_mut(index) {
+            body.verlet_mut().set_velocity(velocity, sub_dt);
+        }
+    }
+
+    pub fn apply_impulse(&mut self, index: usize, impulse: Vec2) {
+        let sub_dt = self.sub_dt();
+        if let Some(body) = self.bodies.get_mut(index) {
+            body.verlet_mut().apply_impulse(impulse, sub_dt);
+        }
+    }
+
+    // a kinematic body gets to position by the end of the next fixed step, set every step
+    // for scripted motion or set its velocity to keep it going
+    pub fn move_kinematic(&mut self, index: usize, position: Vec2) {
+        let time_step = self.time_step;
+        if let Some(body) = self.bodies.get_mut(index) {
+            body.verlet_mut().move_to(position, time_step);
+        }
+    }
+
+    pub fn teleport(&mut self, index: usize, position: Vec2) {
+        if let Some(body) = self.bodies.get_mut(index) {
+            body.verlet_mut().teleport(position);
+            self.invalidate_queries();
+        }
+    }
+
+    // queries rebuild their quadtree on next use, done by every method that moves bodies
+    pub fn invalidate_queries(&mut self) {
+        self.query_tree.take();
+    }
+
+    // the broad phase the queries share, built once between changes to the bodies
+    fn query_tree(&self) -> &(QuadTree, f32) {
+        self.query_tree.get_or_init(|| self.broad_phase())
+    }
+
+    // first body something refers to that isn't there, the solver indexes bodies directly
+    pub(crate) fn missing_body(&self) -> Option<usize> {
+        self.constraints
+            .iter()
+            .flat_map(|constraint| [constraint.a, constraint.b])
+            .chain(
+                self.angle_constraints
+                    .iter()
+                    .flat_map(|constraint| [constraint.a, constraint.b, constraint.c]),
+            )
+            .chain(self.springs.iter().flat_map(|spring| [spring.a, spring.b]))
+            .chain(
+                self.pressure_constraints
+                    .iter()
+                    .flat_map(|constraint| constraint.particles.iter().copied()),
+            )
+            .chain(
+                self.clusters
+                    .iter()
+                    .flat_map(|cluster| cluster.particles.iter().copied()),
+            )
+            .chain(self.mouse_joint.iter().map(|joint| joint.body))
+            .find(|index| *index >= self.bodies.len())
+    }
+
+    // quadtree over the current body positions along with the largest body radius,
+    // ranges queried against it need to be grown by that radius to find every overlap
+    pub fn broad_phase(&self) -> (QuadTree, f32) {
+        let bounds = Rectangle::bounding(
+            self.bodies
+                .iter()
+                .map(|body| body.verlet().current_position),
+        );
+        let max_radius = self
+            .bodies
+            .iter()
+            .map(|body| body.verlet().radius)
+            .fold(0.0, f32::max);
+
+        let mut quadtree = QuadTree::new(bounds, 32, 1, 64);
+        for (index, body) in self.bodies.iter().enumerate() {
+            quadtree.insert(&body.verlet().current_position, index);
+        }
+
+        (quadtree, max_radius)
+    }
+
+    // pushes every body within radius of center away from it and returns the bodies hit,
+    // with line_of_sight pinned and kinematic bodies in between shield the ones behind them
+    pub fn explode(
+        &mut self,
+        center: Vec2,
+        radius: f32,
+        strength: f32,
+        falloff: Falloff,
+        line_of_sight: bool,
+    ) -> Vec<usize> {
+        let (quadtree, max_radius) = self.broad_phase();
+        let reach = radius + max_radius;
+        let range = Rectangle::new(center.x - reach, center.y - reach, reach * 2.0, reach * 2.0);
+
+        let mut candidates = quadtree.query(&range);
+        candidates.sort_unstable();
+
+        let shields: Vec<usize> = if line_of_sight {
+            candidates
+                .iter()
+                .copied()
+                .filter(|index| self.bodies[*index].verlet().inverse_mass() == 0.0)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let sub_dt = self.sub_dt();
+        let mut hit = Vec::new();
+
+        for index in candidates {
+            let body = self.bodies[index].verlet();
+            let diff = body.current_position - center;
+            let dist = diff.length();
+            if dist == 0.0 || dist - body.radius > radius || body.inverse_mass() == 0.0 {
+                continue;
+            }
+
+            let ray = Ray::new(center, diff);
+            let blocked = shields.iter().any(|shield| {
+                let shield = self.bodies[*shield].verlet();
+                match ray.intersect_circle(shield.current_position, shield.radius) {
+                    Some(distance) => distance < dist - body.radius,
+                    None => false,
+                }
+            });
+            if blocked {
+                continue;
+            }
+
+            let impulse = diff / dist * (strength * falloff.weight(dist - body.radius, radius));
+            self.bodies[index]
+                .verlet_mut()
+                .apply_impulse(impulse, sub_dt);
+            hit.push(index);
+        }
+
+        hit
+    }
+
+    // closest body along the ray within max_distance whose layer shares a bit with mask
+    pub fn raycast(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        mask: u32,
+    ) -> Option<RaycastHit> {
+        self.raycast_all(origin, direction, max_distance, mask)
+            .into_iter()
+            .next()
+    }
+
+    // every body along the ray, nearest first, a ray starting inside a body hits it at distance 0
+    pub fn raycast_all(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        mask: u32,
+    ) -> Vec<RaycastHit> {
+        if direction.length() == 0.0 {
+            return Vec::new();
+        }
+        self.sweep(
+            self.query_tree(),
+            origin,
+            direction,
+            max_distance,
+            0.0,
+            mask,
+        )
+    }
+
+    // first body a circle moving from start to end runs into, time is the fraction of the
+    // way it gets before touching, pinned bodies stand in for static geometry and the solver
+    // boundary stops the circle whatever the mask
+    pub fn circle_cast(
+        &self,
+        start: Vec2,
+        end: Vec2,
+        radius: f32,
+        mask: u32,
+    ) -> Option<ShapeCastHit> {
+        let delta = end - start;
+        let length = delta.length();
+        let hit = self
+            .sweep(self.query_tree(), start, delta, length, radius, mask)
+            .into_iter()
+            .next()
+            .map(|hit| {
+                let time = if length > 0.0 {
+                    hit.distance / length
+                } else {
+                    0.0
+                };
+                ShapeCastHit {
+                    body: Some(hit.body),
+                    time,
+                    position: start + delta * time,
+                    point: hit.point,
+                    normal: hit.normal,
+                    user_data: hit.user_data,
+                }
+            });
+
+        let boundary = self.solver.boundary();
+        match boundary.exit(start, delta, radius) {
+            Some(time) if hit.is_none_or(|hit| time < hit.time) => {
+                let position = start + delta * time;
+                let offset = position - boundary.center;
+                let outwards = offset / offset.length();
+                Some(ShapeCastHit {
+                    body: None,
+                    time,
+                    position,
+                    point: position + outwards * radius,
+                    normal: outwards * -1.0,
+                    user_data: 0,
+                })
+            }
+            _ => hit,
+        }
+    }
+
+    // a circle swept along the ray hits a body where the ray hits the body grown by the
+    // circle's radius, the hit point is where the two surfaces touch
+    pub(crate) fn sweep(
+        &self,
+        broad_phase: &(QuadTree, f32),
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        radius: f32,
+        mask: u32,
+    ) -> Vec<RaycastHit> {
+        let ray = Ray::new(origin, direction);
+        let (quadtree, max_radius) = broad_phase;
+        let mut candidates = quadtree.query_ray(&ray, max_distance, max_radius + radius);
+        candidates.sort_unstable();
+
+        let mut hits: Vec<RaycastHit> = candidates
+            .into_iter()
+            .filter_map(|index| {
+                let body = self.bodies[index].verlet();
+                if body.layer & mask == 0 {
+                    return None;
+                }
+
+                let distance = ray.intersect_circle(body.current_position, body.radius + radius)?;
+                if distance > max_distance {
+                    return None;
+                }
+
+                let center = ray.at(distance);
+                let offset = center - body.current_position;
+                let normal = if offset.length() > 0.0 {
+                    offset / offset.length()
+                } else {
+                    ray.direction * -1.0
+                };
+
+                Some(RaycastHit {
+                    body: index,
+                    point: center - normal * radius,
+                    normal,
+                    distance,
+                    user_data: body.user_data,
+                })
+            })
+            .collect();
+
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    // bodies containing the point
+    pub fn query_point(&self, point: Vec2, mask: u32) -> Vec<Overlap> {
+        self.overlapping(Rectangle::new(point.x, point.y, 0.0, 0.0), mask, |body| {
+            (body.current_position - point).length() <= body.radius
+        })
+    }
+
+    pub fn query_circle(&self, center: Vec2, radius: f32, mask: u32) -> Vec<Overlap> {
+        let range = Rectangle::new(
+            center.x - radius,
+            center.y - radius,
+            radius * 2.0,
+            radius * 2.0,
+        );
+        self.overlapping(range, mask, |body| {
+            (body.current_position - center).length() <= body.radius + radius
+        })
+    }
+
+    // a rectangle dragged up or left has a negative size, it covers the same area
+    pub fn query_rectangle(&self, rectangle: Rectangle, mask: u32) -> Vec<Overlap> {
+        let range = Rectangle::new(
+            rectangle
+                .position
+                .x
+                .min(rectangle.position.x + rectangle.width),
+            rectangle
+                .position
+                .y
+                .min(rectangle.position.y + rectangle.height),
+            rectangle.width.abs(),
+            rectangle.height.abs(),
+        );
+        self.overlapping(range, mask, |body| {
+            let closest = Vec2::new(
+                body.current_position
+                    .x
+                    .clamp(range.position.x, range.position.x + range.width),
+                body.current_position
+                    .y
+                    .clamp(range.position.y, range.position.y + range.height),
+            );
+            (body.current_position - closest).length() <= body.radius
+        })
+    }
+
+    // the k bodies whose centers are closest to point, nearest first
+    pub fn nearest(&self, point: Vec2, k: usize, mask: u32) -> Vec<Overlap> {
+        self.closest(point, mask, |found, _| found < k)
+    }
+
+    // bodies whose centers are within radius of point, nearest first
+    pub fn within_radius(&self, point: Vec2, radius: f32, mask: u32) -> Vec<Overlap> {
+        self.closest(point, mask, |_, distance| distance <= radius)
+    }
+
+    // bodies on the mask outwards from point for as long as more, given how many were
+    // found so far and the distance to the next one, holds
+    fn closest<F>(&self, point: Vec2, mask: u32, more: F) -> Vec<Overlap>
+    where
+        F: Fn(usize, f32) -> bool,
+    {
+        let (quadtree, _) = self.query_tree();
+        let mut result = Vec::new();
+        quadtree.best_first(&point, |index, distance| {
+            if !more(result.len(), distance) {
+                return false;
+            }
+            let body = self.bodies[index].verlet();
+            if body.layer & mask != 0 {
+                result.push(Overlap {
+                    body: index,
+                    user_data: body.user_data,
+                });
+            }
+            true
+        });
+        result
+    }
+
+    // broad phase over the range grown by the largest radius, then the exact test per body
+    fn overlapping<F>(&self, range: Rectangle, mask: u32, overlaps: F) -> Vec<Overlap>
+    where
+        F: Fn(&VerletObject) -> bool,
+    {
+        let (quadtree, max_radius) = self.query_tree();
+        let grown = Rectangle::new(
+            range.position.x - max_radius,
+            range.position.y - max_radius,
+            range.width + max_radius * 2.0,
+            range.height + max_radius * 2.0,
+        );
+
+        let mut candidates = quadtree.query(&grown);
+        candidates.sort_unstable();
+
+        candidates
+            .into_iter()
+            .map(|index| (index, self.bodies[index].verlet()))
+            .filter(|(_, body)| body.layer & mask != 0 && overlaps(body))
+            .map(|(index, body)| Overlap {
+                body: index,
+                user_data: body.user_data,
+            })
+            .collect()
+    }
+
+    // grabs the movable body under the point whose center is closest to it
+    pub fn begin_drag(
+        &mut self,
+        point: Vec2,
+        stiffness: f32,
+        max_force: Option<f32>,
+    ) -> Option<usize> {
+        let body = self
+            .query_point(point, u32::MAX)
+            .into_iter()
+            .map(|overlap| overlap.body)
+            .filter(|index| self.bodies[*index].verlet().inverse_mass() > 0.0)
+            .min_by(|a, b| {
+                let a = (self.bodies[*a].verlet().current_position - point).length();
+                let b = (self.bodies[*b].verlet().current_position - point).length();
+                a.total_cmp(&b)
+            })?;
+
+        let anchor = point - self.bodies[body].verlet().current_position;
+        self.mouse_joint = Some(MouseJoint::new(body, anchor, point, stiffness, max_force));
+        Some(body)
+    }
+
+    pub fn update_drag(&mut self, point: Vec2) {
+        if let Some(joint) = self.mouse_joint.as_mut() {
+            joint.target = point;
+        }
+    }
+
+    pub fn end_drag(&mut self) {
+        self.mouse_joint = None;
+    }
+
+    // break events only cover the latest update
+    pub fn update(&mut self, dt: f32) {
+        let dt = if self.deterministic {
+            self.time_step
+        } else {
+            dt
+        };
+        self.break_events.clear();
+        self.previous_positions = self
+            .bodies
+            .iter()
+            .map(|body| body.verlet().current_position)
+            .collect();
+        let solver = self.solver;
+        solver.update(dt, self);
+        self.invalidate_queries();
+    }
+
+    // runs as many fixed time steps as fit into the elapsed time and keeps the rest for
+    // the next call, returns how far the leftover time is into the next step (0..1)
+    pub fn advance(&mut self, elapsed: f32) -> f32 {
+        // a step that isn't positive would never use up the accumulator
+        if !(self.time_step.is_finite() && self.time_step > 0.0) {
+            return 0.0;
+        }
+        self.accumulator += elapsed.clamp(0.0, MAX_FRAME_TIME);
+
+        let mut break_events = Vec::new();
+        while self.accumulator >= self.time_step {
+            self.update(self.time_step);
+            self.accumulator -= self.time_step;
+            break_events.append(&mut self.break_events);
+        }
+        self.break_events = break_events;
+
+        self.alpha()
+    }
+
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.time_step
+    }
+
+    // position between the last two fixed steps for rendering at alpha
+    pub fn interpolated_position(&self, index: usize) -> Option<Vec2> {
+        let body = self.bodies.get(index)?.verlet();
+        let alpha = self.alpha();
+        Some(match self.previous_positions.get(index) {
+            Some(previous) => *previous * (1.0 - alpha) + body.current_position * alpha,
+            None => body.current_position,
+        })
+    }
+}
+
+// longest frame advance will catch up on, so a stall doesn't snowball into more steps
+const MAX_FRAME_TIME: f32 = 0.25;
+
+pub(crate) fn default_time_step() -> f32 {
+    0.016
+}
+
+impl<B> Default for World<B> {
+    fn default() -> World<B> {
+        World {
+            bodies: Vec::new(),
+            constraints: Vec::new(),
+            angle_constraints: Vec::new(),
+            pressure_constraints: Vec::new(),
+            clusters: Vec::new(),
+            springs: Vec::new(),
+            force_fields: Vec::new(),
+            solver: Solver::new(),
+            time_step: default_time_step(),
+            accumulator: 0.0,
+            previous_positions: Vec::new(),
+            break_events: Vec::new(),
+            mouse_joint: None,
+            deterministic: false,
+            query_tree: OnceCell::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::world::World,
+        fields::force_field::Falloff,
+        geometry::{
+            integrator::Integrator,
+            rectangle::Rectangle,
+            vector::Vec2,
+            verlet::{VerletObject, Vervelt},
+        },
+        queries::overlap::Overlap,
+    };
+
+    struct Ember {
+        body: VerletObject,
+        temperature: f32,
+    }
+
+    impl Vervelt for Ember {
+        fn verlet(&self) -> &VerletObject {
+            &self.body
+        }
+
+        fn verlet_mut(&mut self) -> &mut VerletObject {
+            &mut self.body
+        }
+
+        fn update_position(&mut self, dt: f32, air_damping: f32, integrator: Integrator) {
+            self.temperature -= dt;
+            self.body.integrate(dt, air_damping, integrator);
+        }
+    }
+
+    impl From<VerletObject> for Ember {
+        fn from(body: VerletObject) -> Ember {
+            Ember {
+                body,
+                temperature: 1.0,
+            }
+        }
+    }
+
+    #[test]
+    fn custom_bodies() {
+        let mut world = World::<Ember>::default();
+        let a = world.add_body(VerletObject::new(Vec2::new(300.0, 100.0), 5.0).into());
+        let b = world.add_body(VerletObject::new(Vec2::new(303.0, 100.0), 5.0).into());
+
+        for _ in 0..10 {
+            world.update(0.016);
+        }
+
+        let (a, b) = (&world.bodies[a], &world.bodies[b]);
+        assert!(a.body.current_position.y > 100.0);
+        assert!((a.body.current_position - b.body.current_position).length() >= 9.99);
+        assert!((a.temperature - (1.0 - 0.16)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn raycast_hits_nearest() {
+        let mut world = World::new();
+        let far = world.add_body(VerletObject::new(Vec2::new(200.0, 100.0), 10.0));
+        let near = world.add_body(VerletObject::new(Vec2::new(100.0, 102.0), 10.0));
+        let mut hidden = VerletObject::new(Vec2::new(150.0, 100.0), 10.0);
+        hidden.layer = 2;
+        hidden.user_data = 7;
+        let hidden = world.add_body(hidden);
+        world.add_body(VerletObject::new(Vec2::new(100.0, 300.0), 10.0));
+
+        let hit = world
+            .raycast(Vec2::new(0.0, 100.0), Vec2::new(1.0, 0.0), 500.0, 1)
+            .unwrap();
+        assert!(hit.body == near);
+        assert!((hit.point - Vec2::new(hit.distance, 100.0)).length() < 1e-3);
+        assert!((hit.point - (Vec2::new(100.0, 102.0) + hit.normal * 10.0)).length() < 1e-3);
+
+        let hits = world.raycast_all(Vec2::new(0.0, 100.0), Vec2::new(1.0, 0.0), 500.0, u32::MAX);
+        let bodies: Vec<usize> = hits.iter().map(|hit| hit.body).collect();
+        assert!(bodies == vec![near, hidden, far]);
+        assert!(hits[1].user_data == 7);
+
+        assert!(world
+            .raycast(Vec2::new(0.0, 100.0), Vec2::new(1.0, 0.0), 50.0, u32::MAX)
+            .is_none());
+        assert!(world
+            .raycast(Vec2::new(0.0, 100.0), Vec2::new(-1.0, 0.0), 500.0, u32::MAX)
+            .is_none());
+    }
+
+    #[test]
+    fn overlap_queries() {
+        let mut world = World::new();
+        let a = world.add_body(VerletObject::new(Vec2::new(100.0, 100.0), 10.0));
+        let b = world.add_body(VerletObject::new(Vec2::new(130.0, 100.0), 10.0));
+        let mut c = VerletObject::new(Vec2::new(200.0, 200.0), 30.0);
+        c.layer = 2;
+        let c = world.add_body(c);
+
+        let bodies = |overlaps: Vec<Overlap>| -> Vec<usize> {
+            overlaps.iter().map(|overlap| overlap.body).collect()
+        };
+
+        assert!(bodies(world.query_point(Vec2::new(105.0, 105.0), u32::MAX)) == vec![a]);
+        assert!(bodies(world.query_point(Vec2::new(115.0, 100.0), u32::MAX)).is_empty());
+        assert!(bodies(world.query_point(Vec2::new(220.0, 200.0), 1)).is_empty());
+        assert!(bodies(world.query_point(Vec2::new(220.0, 200.0), 2)) == vec![c]);
+
+        let circle = world.query_circle(Vec2::new(115.0, 100.0), 6.0, u32::MAX);
+        assert!(bodies(circle) == vec![a, b]);
+
+        // only touches the edge of c, its center is far outside the rectangle
+        let rectangle = Rectangle::new(175.0, 150.0, -75.0, 40.0);
+        assert!(bodies(world.query_rectangle(rectangle, u32::MAX)) == vec![c]);
+        let rectangle = Rectangle::new(90.0, 90.0, 100.0, 20.0);
+        assert!(bodies(world.query_rectangle(rectangle, u32::MAX)) == vec![a, b]);
+    }
+
+    #[test]
+    fn queries_share_one_tree_per_step() {
+        let mut world = World::new();
+        let body = world.add_body(VerletObject::new(Vec2::new(100.0, 100.0), 10.0));
+
+        let tree = world.query_tree() as *const _;
+        assert!(world.query_point(Vec2::new(100.0, 100.0), u32::MAX).len() == 1);
+        assert!(std::ptr::eq(world.query_tree(), tree));
+
+        world.teleport(body, Vec2::new(200.0, 100.0));
+        assert!(world
+            .query_point(Vec2::new(100.0, 100.0), u32::MAX)
+            .is_empty());
+        assert!(world.query_point(Vec2::new(200.0, 100.0), u32::MAX).len() == 1);
+
+        let other = world.add_body(VerletObject::new(Vec2::new(400.0, 100.0), 10.0));
+        let hit = world.raycast(
+            Vec2::new(500.0, 100.0),
+            Vec2::new(-1.0, 0.0),
+            200.0,
+            u32::MAX,
+        );
+        assert!(hit.unwrap().body == other);
+
+        world.set_velocity(other, Vec2::new(0.0, 1000.0));
+        world.update(0.016);
+        let moved = world.bodies[other].current_position;
+        assert!(world.query_point(moved, u32::MAX).len() == 1);
+    }
+
+    #[test]
+    fn nearest_bodies() {
+        let mut world = World::new();
+        let a = world.add_body(VerletObject::new(Vec2::new(100.0, 100.0), 10.0));
+        let b = world.add_body(VerletObject::new(Vec2::new(130.0, 100.0), 10.0));
+        let mut c = VerletObject::new(Vec2::new(110.0, 100.0), 5.0);
+        c.layer = 2;
+        let c = world.add_body(c);
+        world.add_body(VerletObject::new(Vec2::new(300.0, 300.0), 10.0));
+
+        let bodies = |overlaps: Vec<Overlap>| -> Vec<usize> {
+            overlaps.iter().map(|overlap| overlap.body).collect()
+        };
+
+        let point = Vec2::new(105.0, 100.0);
+        assert!(bodies(world.nearest(point, 2, u32::MAX)) == vec![a, c]);
+        assert!(bodies(world.nearest(point, 2, 1)) == vec![a, b]);
+        assert!(bodies(world.nearest(point, 1, 2)) == vec![c]);
+        assert!(world.nearest(point, 0, u32::MAX).is_empty());
+
+        assert!(bodies(world.within_radius(point, 30.0, u32::MAX)) == vec![a, c, b]);
+        assert!(bodies(world.within_radius(point, 30.0, 1)) == vec![a, b]);
+        assert!(bodies(world.within_radius(point, 4.0, u32::MAX)).is_empty());
+    }
+
+    #[test]
+    fn circle_cast_stops_at_contact() {
+        let mut world = World::new();
+        let mut wall = VerletObject::new(Vec2::new(200.0, 100.0), 20.0);
+        wall.pinned = true;
+        wall.user_data = 3;
+        let wall = world.add_body(wall);
+        let mut ghost = VerletObject::new(Vec2::new(150.0, 100.0), 10.0);
+        ghost.layer = 2;
+        world.add_body(ghost);
+
+        let hit = world
+            .circle_cast(Vec2::new(100.0, 100.0), Vec2::new(300.0, 100.0), 10.0, 1)
+            .unwrap();
+        assert!(hit.body == Some(wall) && hit.user_data == 3);
+        assert!((hit.time - 0.35).abs() < 1e-4);
+        assert!((hit.position - Vec2::new(170.0, 100.0)).length() < 1e-3);
+        assert!((hit.point - Vec2::new(180.0, 100.0)).length() < 1e-3);
+        assert!((hit.normal - Vec2::new(-1.0, 0.0)).length() < 1e-4);
+
+        // grazing past the wall only hits with a wide enough circle
+        let start = Vec2::new(100.0, 135.0);
+        let end = Vec2::new(300.0, 135.0);
+        assert!(world.circle_cast(start, end, 10.0, 1).is_none());
+        assert!(world.circle_cast(start, end, 20.0, 1).is_some());
+
+        let hit = world
+            .circle_cast(Vec2::new(215.0, 100.0), Vec2::new(300.0, 100.0), 10.0, 1)
+            .unwrap();
+        assert!(hit.time == 0.0);
+        assert!(world
+            .circle_cast(Vec2::new(100.0, 100.0), Vec2::new(160.0, 100.0), 10.0, 1)
+            .is_none());
+    }
+
+    #[test]
+    fn circle_cast_stops_at_the_boundary() {
+        let mut world = World::new();
+        let start = Vec2::new(300.0, 300.0);
+
+        let hit = world
+            .circle_cast(start, Vec2::new(700.0, 300.0), 10.0, u32::MAX)
+            .unwrap();
+        assert!(hit.body.is_none());
+        assert!((hit.time - 0.725).abs() < 1e-4);
+        assert!((hit.position - Vec2::new(590.0, 300.0)).length() < 1e-3);
+        assert!((hit.point - Vec2::new(600.0, 300.0)).length() < 1e-3);
+        assert!((hit.normal - Vec2::new(-1.0, 0.0)).length() < 1e-4);
+
+        // a body in front of the boundary is hit first, one behind it is never reached
+        let near = world.add_body(VerletObject::new(Vec2::new(500.0, 300.0), 10.0));
+        let hit = world
+            .circle_cast(start, Vec2::new(700.0, 300.0), 10.0, u32::MAX)
+            .unwrap();
+        assert!(hit.body == Some(near));
+
+        let hit = world
+            .circle_cast(start, Vec2::new(700.0, 300.0), 10.0, 2)
+            .unwrap();
+        assert!(hit.body.is_none());
+    }
+
+    #[test]
+    fn kinematic_bodies_push_but_are_not_pushed() {
+        let mut world = World::new();
+        let mut paddle = VerletObject::new(Vec2::new(300.0, 400.0), 20.0);
+        paddle.kinematic = true;
+        let paddle = world.add_body(paddle);
+        let ball = world.add_body(VerletObject::new(Vec2::new(300.0, 370.0), 10.0));
+
+        for step in 1..=25 {
+            world.move_kinematic(paddle, Vec2::new(300.0, 400.0 - step as f32 * 2.0));
+            world.update(world.time_step);
+        }
+
+        assert!(world.bodies[paddle].current_position == Vec2::new(300.0, 350.0));
+        assert!((world.velocity(paddle).unwrap() - Vec2::new(0.0, -125.0)).length() < 1e-2);
+        assert!(world.bodies[ball].current_position.y < 321.0);
+
+        // arrived at its target it stands still
+        world.update(world.time_step);
+        assert!(world.bodies[paddle].current_position == Vec2::new(300.0, 350.0));
+        assert!(world.velocity(paddle).unwrap().length() == 0.0);
+    }
+
+    #[test]
+    fn fixed_steps() {
+        let mut world = World::new();
+        let body = world.add_body(VerletObject::new(Vec2::new(300.0, 100.0), 5.0));
+
+        let alpha = world.advance(0.04);
+        assert!((alpha - 0.5).abs() < 1e-3);
+        assert!(world.bodies[body].last_dt == world.sub_dt());
+
+        let previous = world.previous_positions[body];
+        let current = world.bodies[body].current_position;
+        let interpolated = world.interpolated_position(body).unwrap();
+        assert!((interpolated - (previous + current) * 0.5).length() < 1e-3);
+
+        world.advance(0.009);
+        assert!((world.alpha() - 0.0625).abs() < 1e-3);
+        assert!(world.previous_positions[body] == current);
+
+        let resting = world.bodies[body].current_position;
+        world.time_step = 0.0;
+        assert!(world.advance(0.04) == 0.0);
+        assert!(world.bodies[body].current_position == resting);
+    }
+
+    #[test]
+    fn explosion_pushes_outwards() {
+        let mut world = World::new();
+        let left = world.add_body(VerletObject::new(Vec2::new(280.0, 300.0), 5.0));
+        let right = world.add_body(VerletObject::new(Vec2::new(330.0, 300.0), 5.0));
+        let far = world.add_body(VerletObject::new(Vec2::new(500.0, 300.0), 5.0));
+
+        let hit = world.explode(Vec2::new(300.0, 300.0), 50.0, 500.0, Falloff::Linear, false);
+
+        assert!(hit == vec![left, right]);
+        assert!(world.velocity(left).unwrap().x < 0.0);
+        assert!(world.velocity(right).unwrap().x > 0.0);
+        assert!(world.velocity(left).unwrap().x.abs() > world.velocity(right).unwrap().x);
+        assert!(world.velocity(far).unwrap() == Vec2::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn pinned_bodies_block_explosions() {
+        let mut world = World::new();
+        let mut wall = VerletObject::new(Vec2::new(320.0, 300.0), 5.0);
+        wall.pinned = true;
+        world.add_body(wall);
+        let behind = world.add_body(VerletObject::new(Vec2::new(340.0, 300.0), 5.0));
+        let beside = world.add_body(VerletObject::new(Vec2::new(300.0, 330.0), 5.0));
+
+        let hit = world.explode(
+            Vec2::new(300.0, 300.0),
+            80.0,
+            500.0,
+            Falloff::Constant,
+            true,
+        );
+
+        assert!(hit == vec![beside]);
+        assert!(world.velocity(behind).unwrap() == Vec2::new(0.0, 0.0));
+    }
+}
@@ -0,0 +1,168 @@
+use crate::geometry::{rectangle::Rectangle, vector::Vec2, verlet::Vervelt};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Falloff {
+    Constant,
+    Linear,
+    Quadratic,
+}
+
+impl Falloff {
+    // weight at dist inside a field of the given radius, zero outside of it
+    pub fn weight(self, dist: f32, radius: f32) -> f32 {
+        if dist > radius || radius <= 0.0 {
+            return 0.0;
+        }
+
+        let t = 1.0 - dist / radius;
+        match self {
+            Falloff::Constant => 1.0,
+            Falloff::Linear => t,
+            Falloff::Quadratic => t * t,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ForceField {
+    // positive strength attracts, negative strength repels
+    Radial {
+        center: Vec2,
+        radius: f32,
+        strength: f32,
+        falloff: Falloff,
+    },
+    Wind {
+        area: Rectangle,
+        acceleration: Vec2,
+    },
+    // positive strength spins clockwise on screen, y grows downwards like gravity does
+    Vortex {
+        center: Vec2,
+        radius: f32,
+        strength: f32,
+        falloff: Falloff,
+    },
+}
+
+impl ForceField {
+    pub fn acceleration_at(&self, position: Vec2) -> Vec2 {
+        match *self {
+            ForceField::Radial {
+                center,
+                radius,
+                strength,
+                falloff,
+            } => {
+                let diff = center - position;
+                let dist = diff.length();
+                if dist == 0.0 {
+                    return Vec2::new(0.0, 0.0);
+                }
+                diff / dist * (strength * falloff.weight(dist, radius))
+            }
+            ForceField::Wind { area, acceleration } => {
+                if area.contains(&position) {
+                    acceleration
+                } else {
+                    Vec2::new(0.0, 0.0)
+                }
+            }
+            ForceField::Vortex {
+                center,
+                radius,
+                strength,
+                falloff,
+            } => {
+                let diff = position - center;
+                let dist = diff.length();
+                if dist == 0.0 {
+                    return Vec2::new(0.0, 0.0);
+                }
+                Vec2::new(-diff.y, diff.x) / dist * (strength * falloff.weight(dist, radius))
+            }
+        }
+    }
+
+    pub fn apply<B: Vervelt>(&self, bodies: &mut [B]) {
+        for body in bodies {
+            let acceleration = self.acceleration_at(body.verlet().current_position);
+            body.accelerate(acceleration);
+        }
+    }
+
+    pub fn set_position(&mut self, position: Vec2) {
+        match self {
+            ForceField::Radial { center, .. } | ForceField::Vortex { center, .. } => {
+                *center = position
+            }
+            ForceField::Wind { area, .. } => area.position = position,
+        }
+    }
+
+    pub fn set_strength(&mut self, value: f32) {
+        match self {
+            ForceField::Radial { strength, .. } | ForceField::Vortex { strength, .. } => {
+                *strength = value
+            }
+            ForceField::Wind { acceleration, .. } => {
+                let length = acceleration.length();
+                if length > 0.0 {
+                    *acceleration = *acceleration / length * value;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fields::force_field::{Falloff, ForceField},
+        geometry::{rectangle::Rectangle, vector::Vec2},
+    };
+
+    #[test]
+    fn radial_falloff() {
+        let attractor = ForceField::Radial {
+            center: Vec2::new(0.0, 0.0),
+            radius: 100.0,
+            strength: 50.0,
+            falloff: Falloff::Linear,
+        };
+
+        assert!(attractor.acceleration_at(Vec2::new(50.0, 0.0)) == Vec2::new(-25.0, 0.0));
+        assert!(attractor.acceleration_at(Vec2::new(0.0, 150.0)) == Vec2::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn wind_only_inside_area() {
+        let wind = ForceField::Wind {
+            area: Rectangle::new(0.0, 0.0, 10.0, 10.0),
+            acceleration: Vec2::new(5.0, 0.0),
+        };
+
+        assert!(wind.acceleration_at(Vec2::new(5.0, 5.0)) == Vec2::new(5.0, 0.0));
+        assert!(wind.acceleration_at(Vec2::new(15.0, 5.0)) == Vec2::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn vortex_is_tangential() {
+        let vortex = ForceField::Vortex {
+            center: Vec2::new(0.0, 0.0),
+            radius: 100.0,
+            strength: 10.0,
+            falloff: Falloff::Constant,
+        };
+        let position = Vec2::new(30.0, 40.0);
+
+        assert!(vortex.acceleration_at(position).dot(position).abs() < 1e-4);
+        assert!((vortex.acceleration_at(position).length() - 10.0).abs() < 1e-4);
+
+        // clockwise on screen: right of the center is pushed down, below it to the left
+        assert!(vortex.acceleration_at(Vec2::new(50.0, 0.0)) == Vec2::new(0.0, 10.0));
+        assert!(vortex.acceleration_at(Vec2::new(0.0, 50.0)) == Vec2::new(-10.0, 0.0));
+    }
+}
@@ -0,0 +1 @@
+pub mod force_field;
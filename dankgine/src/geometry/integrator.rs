@@ -0,0 +1,12 @@
+use wasm_bindgen::prelude::*;
+
+// position verlet keeps the velocity implicit in the last position step, the other two
+// carry an explicit velocity, velocity verlet measures it at whole steps
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum Integrator {
+    #[default]
+    PositionVerlet,
+    VelocityVerlet,
+    SemiImplicitEuler,
+}
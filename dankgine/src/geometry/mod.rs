@@ -1,3 +1,5 @@
+pub mod integrator;
+pub mod ray;
+pub mod rectangle;
 pub mod vector;
 pub mod verlet;
-pub mod rectangle;
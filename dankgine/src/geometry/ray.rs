@@ -0,0 +1,84 @@
+use super::{rectangle::Rectangle, vector::Vec2};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Vec2,
+    pub direction: Vec2,
+}
+
+impl Ray {
+    // direction gets normalized so hit distances are in world units
+    pub fn new(origin: Vec2, direction: Vec2) -> Ray {
+        let length = direction.length();
+        let direction = if length > 0.0 {
+            direction / length
+        } else {
+            direction
+        };
+        Ray { origin, direction }
+    }
+
+    // distance along the ray to where it enters the circle, zero if it starts inside
+    pub fn intersect_circle(&self, center: Vec2, radius: f32) -> Option<f32> {
+        let to_center = center - self.origin;
+        if to_center.length() <= radius {
+            return Some(0.0);
+        }
+
+        let projection = to_center.dot(self.direction);
+        if projection < 0.0 {
+            return None;
+        }
+
+        let closest_squared = to_center.dot(to_center) - projection * projection;
+        let radius_squared = radius * radius;
+        if closest_squared > radius_squared {
+            return None;
+        }
+
+        Some(projection - (radius_squared - closest_squared).sqrt())
+    }
+
+    pub fn at(&self, distance: f32) -> Vec2 {
+        self.origin + self.direction * distance
+    }
+
+    // slab test, distance along the ray to where it enters the rectangle, zero if it starts inside
+    pub fn intersect_rectangle(&self, rectangle: &Rectangle) -> Option<f32> {
+        let axes = [
+            (
+                self.origin.x,
+                self.direction.x,
+                rectangle.position.x,
+                rectangle.position.x + rectangle.width,
+            ),
+            (
+                self.origin.y,
+                self.direction.y,
+                rectangle.position.y,
+                rectangle.position.y + rectangle.height,
+            ),
+        ];
+
+        let mut near: f32 = 0.0;
+        let mut far = f32::MAX;
+        for (origin, direction, min, max) in axes {
+            if direction == 0.0 {
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+
+            let t1 = (min - origin) / direction;
+            let t2 = (max - origin) / direction;
+            near = near.max(t1.min(t2));
+            far = far.min(t1.max(t2));
+            if near > far {
+                return None;
+            }
+        }
+
+        Some(near)
+    }
+}
@@ -1,6 +1,6 @@
 use super::vector::Vec2;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Rectangle {
     pub position: Vec2,
     pub width: f32,
@@ -16,6 +16,24 @@ impl Rectangle {
         }
     }
 
+    // smallest rectangle holding every point, empty at the origin when there are none
+    pub fn bounding<I: IntoIterator<Item = Vec2>>(points: I) -> Rectangle {
+        let mut min = Vec2::new(f32::MAX, f32::MAX);
+        let mut max = Vec2::new(f32::MIN, f32::MIN);
+        for point in points {
+            min.x = min.x.min(point.x);
+            min.y = min.y.min(point.y);
+            max.x = max.x.max(point.x);
+            max.y = max.y.max(point.y);
+        }
+
+        if min.x > max.x {
+            Rectangle::new(0.0, 0.0, 0.0, 0.0)
+        } else {
+            Rectangle::new(min.x, min.y, max.x - min.x, max.y - min.y)
+        }
+    }
+
     pub fn contains(&self, point: &Vec2) -> bool {
         point.x >= self.position.x
             && point.x <= self.position.x + self.width
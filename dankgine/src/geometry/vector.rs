@@ -13,6 +13,19 @@ impl Vec2 {
     pub fn length(self) -> f32 {
         (self.x.powf(2.0) + self.y.powf(2.0)).sqrt()
     }
+
+    pub fn dot(self, other: Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    pub fn cross(self, other: Vec2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn rotate(self, angle: f32) -> Vec2 {
+        let (sin, cos) = angle.sin_cos();
+        Vec2::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
 }
 
 impl Add for Vec2 {
@@ -1,4 +1,4 @@
-use crate::geometry::vector::Vec2;
+use crate::geometry::{integrator::Integrator, vector::Vec2};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, PartialOrd)]
 pub struct VerletObject {
@@ -6,11 +6,59 @@ pub struct VerletObject {
     pub old_position: Vec2,
     pub acceleration: Vec2,
     pub radius: f32,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub damping: f32,
+    #[serde(default)]
+    pub last_dt: f32,
+    #[serde(default)]
+    pub velocity: Vec2,
+    #[serde(default)]
+    pub previous_acceleration: Vec2,
+    // opaque to the engine, lets the game find its own data for a body
+    #[serde(default)]
+    pub user_data: u64,
+    // bit set matched against query masks
+    #[serde(default = "default_layer")]
+    pub layer: u32,
+    // swept against everything else so it can't tunnel when fast
+    #[serde(default)]
+    pub ccd: bool,
+    // moved by user code at its own velocity, pushes other bodies but isn't pushed back
+    #[serde(default)]
+    pub kinematic: bool,
+    #[serde(default)]
+    pub kinematic_target: Option<Vec2>,
 }
 
+pub(crate) fn default_layer() -> u32 {
+    1
+}
+
+// anything carrying a verlet body can be simulated, extra fields just ride along
 pub trait Vervelt {
-    fn update_position(&mut self, dt: f32);
-    fn accelerate(&mut self, acc: Vec2);
+    fn verlet(&self) -> &VerletObject;
+    fn verlet_mut(&mut self) -> &mut VerletObject;
+
+    fn update_position(&mut self, dt: f32, air_damping: f32, integrator: Integrator) {
+        self.verlet_mut().integrate(dt, air_damping, integrator);
+    }
+
+    fn accelerate(&mut self, acc: Vec2) {
+        let body = self.verlet_mut();
+        body.acceleration = body.acceleration + acc;
+    }
+}
+
+impl Vervelt for VerletObject {
+    fn verlet(&self) -> &VerletObject {
+        self
+    }
+
+    fn verlet_mut(&mut self) -> &mut VerletObject {
+        self
+    }
 }
 
 impl VerletObject {
@@ -20,17 +68,271 @@ impl VerletObject {
             old_position: pos,
             acceleration: Vec2::new(0.0, 0.0),
             radius,
+            pinned: false,
+            damping: 0.0,
+            last_dt: 0.0,
+            velocity: Vec2::new(0.0, 0.0),
+            previous_acceleration: Vec2::new(0.0, 0.0),
+            user_data: 0,
+            layer: default_layer(),
+            ccd: false,
+            kinematic: false,
+            kinematic_target: None,
         }
     }
 
-    pub fn update_position(&mut self, dt: f32) {
-        let velocity = self.current_position - self.old_position;
-        self.old_position = self.current_position;
-        self.current_position = self.current_position + velocity + self.acceleration * dt * dt;
+    // damping is a rate per second, the body's own damping adds to the world's air damping
+    pub fn integrate(&mut self, dt: f32, air_damping: f32, integrator: Integrator) {
+        if self.pinned {
+            self.acceleration = Vec2::new(0.0, 0.0);
+            self.velocity = Vec2::new(0.0, 0.0);
+            self.previous_acceleration = Vec2::new(0.0, 0.0);
+            self.last_dt = dt;
+            return;
+        }
+
+        if self.kinematic {
+            self.kinematic_step(dt);
+            self.acceleration = Vec2::new(0.0, 0.0);
+            self.last_dt = dt;
+            return;
+        }
+
+        let retained = (1.0 - (self.damping + air_damping) * dt).max(0.0);
+        match integrator {
+            Integrator::PositionVerlet => self.position_verlet(dt, retained),
+            Integrator::VelocityVerlet => self.velocity_verlet(dt, retained),
+            Integrator::SemiImplicitEuler => self.semi_implicit_euler(dt, retained),
+        }
+
         self.acceleration = Vec2::new(0.0, 0.0);
+        self.last_dt = dt;
+    }
+
+    // forces don't act on kinematic bodies, they keep their velocity until they reach
+    // their target and stand still there
+    fn kinematic_step(&mut self, dt: f32) {
+        let mut step = self.velocity * dt;
+        if let Some(target) = self.kinematic_target {
+            let remaining = target - self.current_position;
+            if remaining.length() <= step.length() {
+                step = remaining;
+            }
+        }
+
+        self.old_position = self.current_position;
+        self.current_position = self.current_position + step;
+        self.velocity = step / dt;
+        self.previous_acceleration = Vec2::new(0.0, 0.0);
+    }
+
+    // time-corrected verlet, the last step is rescaled by how much dt changed since then
+    fn position_verlet(&mut self, dt: f32, retained: f32) {
+        let time_correction = dt / self.step_dt(dt);
+        let step = (self.current_position - self.old_position) * (time_correction * retained);
+        self.old_position = self.current_position;
+        self.current_position = self.current_position + step + self.acceleration * dt * dt;
+        self.velocity = (self.current_position - self.old_position) / dt;
+        self.previous_acceleration = Vec2::new(0.0, 0.0);
+    }
+
+    // the stored velocity assumed the acceleration of the last step carried on,
+    // now that the new one is known the second half of that kick gets fixed up
+    fn velocity_verlet(&mut self, dt: f32, retained: f32) {
+        let velocity = (self.corrected_velocity()
+            + (self.acceleration - self.previous_acceleration) * (0.5 * self.last_dt))
+            * retained;
+        self.old_position = self.current_position;
+        self.current_position =
+            self.current_position + velocity * dt + self.acceleration * (0.5 * dt * dt);
+        self.velocity = velocity + self.acceleration * dt;
+        self.previous_acceleration = self.acceleration;
+    }
+
+    fn semi_implicit_euler(&mut self, dt: f32, retained: f32) {
+        let velocity = (self.corrected_velocity() + self.acceleration * dt) * retained;
+        self.old_position = self.current_position;
+        self.current_position = self.current_position + velocity * dt;
+        self.velocity = velocity;
+        self.previous_acceleration = Vec2::new(0.0, 0.0);
+    }
+
+    // explicit velocity plus whatever constraints and collisions moved the body
+    // on top of where the last step put it
+    fn corrected_velocity(&self) -> Vec2 {
+        if self.last_dt <= 0.0 {
+            return self.velocity;
+        }
+
+        let expected = self.old_position + self.velocity * self.last_dt
+            - self.previous_acceleration * (0.5 * self.last_dt * self.last_dt);
+        self.velocity + (self.current_position - expected) / self.last_dt
+    }
+
+    // dt of the last position step, or the given fallback before the first one
+    pub fn step_dt(&self, fallback: f32) -> f32 {
+        if self.last_dt > 0.0 {
+            self.last_dt
+        } else {
+            fallback
+        }
+    }
+
+    // velocity at the current position, dt is only used if the body hasn't been stepped yet
+    pub fn velocity(&self, dt: f32) -> Vec2 {
+        if self.last_dt <= 0.0 {
+            return (self.current_position - self.old_position) / dt;
+        }
+        self.corrected_velocity()
     }
 
-    pub fn accelerate(&mut self, acc: Vec2) {
-        self.acceleration = self.acceleration + acc;
+    // keeps the implicit and the explicit velocity in agreement
+    pub fn set_velocity(&mut self, velocity: Vec2, dt: f32) {
+        self.kinematic_target = None;
+        self.old_position = self.current_position - velocity * dt;
+        self.velocity = velocity;
+        self.previous_acceleration = Vec2::new(0.0, 0.0);
+        self.last_dt = dt;
+    }
+
+    // bodies have unit mass, so the impulse is the change in velocity
+    pub fn apply_impulse(&mut self, impulse: Vec2, dt: f32) {
+        if self.inverse_mass() == 0.0 {
+            return;
+        }
+        self.old_position = self.old_position - impulse * self.step_dt(dt);
+        self.velocity = self.velocity + impulse;
+    }
+
+    // moves both positions so the velocity is kept as it is
+    pub fn teleport(&mut self, position: Vec2) {
+        let offset = position - self.current_position;
+        self.current_position = position;
+        self.old_position = self.old_position + offset;
+    }
+
+    // heads for target at a velocity that gets it there within duration
+    pub fn move_to(&mut self, target: Vec2, duration: f32) {
+        self.velocity = (target - self.current_position) / duration;
+        self.kinematic_target = Some(target);
+    }
+
+    // share of a positional correction this body takes, pinned and kinematic bodies don't move
+    pub fn inverse_mass(&self) -> f32 {
+        if self.pinned || self.kinematic {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::{
+        integrator::Integrator,
+        vector::Vec2,
+        verlet::{VerletObject, Vervelt},
+    };
+
+    #[test]
+    fn velocity_round_trip() {
+        let mut body = VerletObject::new(Vec2::new(10.0, 10.0), 1.0);
+        body.set_velocity(Vec2::new(100.0, -50.0), 0.002);
+        assert!((body.velocity(0.002) - Vec2::new(100.0, -50.0)).length() < 1e-2);
+
+        body.apply_impulse(Vec2::new(-100.0, 0.0), 0.002);
+        assert!((body.velocity(0.002) - Vec2::new(0.0, -50.0)).length() < 1e-2);
+
+        body.teleport(Vec2::new(200.0, 200.0));
+        assert!(body.current_position == Vec2::new(200.0, 200.0));
+        assert!((body.velocity(0.002) - Vec2::new(0.0, -50.0)).length() < 1e-2);
+    }
+
+    #[test]
+    fn user_data_is_serialized() {
+        let mut body = VerletObject::new(Vec2::new(10.0, 10.0), 1.0);
+        body.user_data = u64::MAX - 1;
+
+        let json = serde_json::to_string(&body).unwrap();
+        let restored: VerletObject = serde_json::from_str(&json).unwrap();
+        assert!(restored.user_data == u64::MAX - 1);
+
+        let old = r#"{"current_position":{"x":1.0,"y":2.0},"old_position":{"x":1.0,"y":2.0},"acceleration":{"x":0.0,"y":0.0},"radius":1.0}"#;
+        let restored: VerletObject = serde_json::from_str(old).unwrap();
+        assert!(restored.user_data == 0);
+    }
+
+    #[test]
+    fn velocity_survives_dt_changes() {
+        let mut body = VerletObject::new(Vec2::new(0.0, 0.0), 1.0);
+        body.set_velocity(Vec2::new(100.0, 0.0), 0.01);
+
+        for dt in [0.02, 0.005, 0.016, 0.001] {
+            body.update_position(dt, 0.0, Integrator::PositionVerlet);
+            assert!((body.velocity(dt).x - 100.0).abs() < 1e-2);
+        }
+        assert!((body.current_position.x - 4.2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn damping_slows_down() {
+        let mut body = VerletObject::new(Vec2::new(0.0, 0.0), 1.0);
+        body.old_position = Vec2::new(-1.0, 0.0);
+        body.damping = 10.0;
+
+        for _ in 0..100 {
+            body.update_position(0.01, 0.0, Integrator::PositionVerlet);
+        }
+        let velocity = body.current_position - body.old_position;
+        assert!(velocity.x > 0.0 && velocity.x < 0.001);
+    }
+
+    #[test]
+    fn terminal_velocity() {
+        let mut body = VerletObject::new(Vec2::new(0.0, 0.0), 1.0);
+        let dt = 0.002;
+
+        for _ in 0..1000 {
+            body.accelerate(Vec2::new(0.0, 1000.0));
+            body.update_position(dt, 10.0, Integrator::PositionVerlet);
+        }
+        let velocity = (body.current_position - body.old_position) / dt;
+        assert!((velocity.y - 100.0).abs() < 2.0);
+    }
+
+    #[test]
+    fn explicit_integrators_free_fall() {
+        let gravity = Vec2::new(0.0, 1000.0);
+
+        for integrator in [Integrator::VelocityVerlet, Integrator::SemiImplicitEuler] {
+            let mut body = VerletObject::new(Vec2::new(0.0, 0.0), 1.0);
+            let mut time = 0.0;
+
+            for dt in [0.01, 0.002, 0.016, 0.005, 0.02] {
+                body.accelerate(gravity);
+                body.update_position(dt, 0.0, integrator);
+                time += dt;
+            }
+
+            assert!((body.velocity(0.01).y - 1000.0 * time).abs() < 1e-2);
+            if integrator == Integrator::VelocityVerlet {
+                assert!((body.current_position.y - 500.0 * time * time).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn explicit_velocity_picks_up_corrections() {
+        let mut body = VerletObject::new(Vec2::new(0.0, 0.0), 1.0);
+        body.set_velocity(Vec2::new(100.0, 0.0), 0.01);
+        body.update_position(0.01, 0.0, Integrator::SemiImplicitEuler);
+
+        // a collision pushing the body back to where it started stops it
+        body.current_position = Vec2::new(0.0, 0.0);
+        assert!(body.velocity(0.01).length() < 1e-3);
+
+        body.update_position(0.01, 0.0, Integrator::SemiImplicitEuler);
+        assert!(body.current_position.length() < 1e-3);
     }
 }
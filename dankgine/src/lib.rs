@@ -1,19 +1,24 @@
 #![feature(test)]
 #[macro_use]
 extern crate serde_derive;
+extern crate bincode;
 extern crate serde_json;
 
 #[macro_use]
 extern crate lazy_static;
 use std::sync::Mutex;
 
-use crate::core::engine::State;
+use crate::core::world::World;
 
+pub mod builders;
 mod collisions;
+pub mod constraints;
 pub mod core;
-mod geometry;
+pub mod fields;
+pub mod geometry;
+pub mod queries;
 mod utils;
 
 lazy_static! {
-    static ref STATE: Mutex<State> = Mutex::new(State::new());
+    static ref STATE: Mutex<World> = Mutex::new(World::new());
 }
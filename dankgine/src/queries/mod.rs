@@ -0,0 +1,3 @@
+pub mod overlap;
+pub mod raycast;
+pub mod shape_cast;
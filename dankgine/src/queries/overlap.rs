@@ -0,0 +1,5 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Overlap {
+    pub body: usize,
+    pub user_data: u64,
+}
@@ -0,0 +1,10 @@
+use crate::geometry::vector::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RaycastHit {
+    pub body: usize,
+    pub point: Vec2,
+    pub normal: Vec2,
+    pub distance: f32,
+    pub user_data: u64,
+}
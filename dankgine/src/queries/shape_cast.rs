@@ -0,0 +1,13 @@
+use crate::geometry::vector::Vec2;
+
+// position is where the cast shape stops, point is where it touches the body, body is none
+// when the shape ran into the solver boundary instead
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShapeCastHit {
+    pub body: Option<usize>,
+    pub time: f32,
+    pub position: Vec2,
+    pub point: Vec2,
+    pub normal: Vec2,
+    pub user_data: u64,
+}
@@ -15,12 +15,16 @@ init().then(() => {
         p5.draw = function () {
             p5.background(0)
 
-            state = JSON.parse(Dankgine.update())
+            const alpha = Dankgine.advance(p5.deltaTime / 1000)
+            state = JSON.parse(Dankgine.state())
             //console.log(state)
 
-            for (const body of state.bodies) {
-                p5.circle(body.current_position.x, body.current_position.y, body.radius * 2)
-            }
+            state.bodies.forEach((body, index) => {
+                const previous = state.previous_positions[index] || body.current_position
+                const x = previous.x + (body.current_position.x - previous.x) * alpha
+                const y = previous.y + (body.current_position.y - previous.y) * alpha
+                p5.circle(x, y, body.radius * 2)
+            })
 
             if (p5.frameCount % 10 === 0) {
                 Dankgine.add_body(200, 50, 5)
@@ -45,7 +49,18 @@ init().then(() => {
         }
 
         p5.mousePressed = function () {
-            Dankgine.add_body(p5.mouseX, p5.mouseY, 10)
+            // grab the body under the cursor, spawn a new one if there is none
+            if (Dankgine.begin_drag(p5.mouseX, p5.mouseY) === undefined) {
+                Dankgine.add_body(p5.mouseX, p5.mouseY, 10)
+            }
+        }
+
+        p5.mouseDragged = function () {
+            Dankgine.update_drag(p5.mouseX, p5.mouseY)
+        }
+
+        p5.mouseReleased = function () {
+            Dankgine.end_drag()
         }
     })
 })
\ No newline at end of file